@@ -1,21 +1,169 @@
-use crate::eval;
-use std::io::{Write, stdin, stdout};
+use num_bigint::BigInt;
+
+use crate::eval::{
+    self, currency, environment,
+    integer::{self, Width},
+    radix, random,
+    rounding::Ties,
+};
+use std::{
+    fs,
+    io::{Write, stdin, stdout},
+};
 
 pub fn run() {
     let mut input = String::new();
 
     loop {
-        print!("> ");
+        match integer::modulus() {
+            Some(modulus) => print!("(mod {}) > ", modulus),
+            None => print!("> "),
+        }
 
         input.clear();
         let _ = stdout().flush();
-        stdin()
+        let read = stdin()
             .read_line(&mut input)
             .expect("Did not enter a string");
+        if read == 0 {
+            break;
+        }
+
+        if let Err(e) = execute(input.trim(), true) {
+            eprintln!("=> {}", e);
+        }
+    }
+}
+
+pub fn script(path: &str) -> Result<(), String> {
+    load(path, true)
+}
+
+fn execute(line: &str, echo: bool) -> Result<(), String> {
+    if let Some(command) = line.strip_prefix(':') {
+        return self::command(command);
+    }
+
+    let n = eval::evaluate(line)?;
+    if echo {
+        // Later lines of a matrix line up under the first.
+        println!("=> {}", n.to_string().replace('\n', "\n   "));
+    }
+
+    Ok(())
+}
+
+fn command(command: &str) -> Result<(), String> {
+    let mut args = command.split_whitespace();
+    match args.next() {
+        Some("load") => match args.next() {
+            Some(path) => load(path, false),
+            None => Err("Missing file: Expected a path after ':load'".into()),
+        },
+        Some("rates") => {
+            let count = match args.next() {
+                Some(path) => currency::load(path)?,
+                None => currency::reload()?,
+            };
+            println!("=> Loaded {} exchange rates", count);
+            Ok(())
+        }
+        Some("base") => {
+            let base = match args.next() {
+                Some(name) => radix::named(name)
+                    .or_else(|| name.parse().ok())
+                    .filter(|base| (2..=36).contains(base))
+                    .ok_or(format!(
+                        "Invalid base '{}': Expected hex, dec, oct, bin or a number between 2 and 36",
+                        name
+                    ))?,
+                None => 10,
+            };
+            environment::fetch_settings().lock().unwrap().base = base;
+            Ok(())
+        }
+        Some("int") => {
+            let width = match args.next() {
+                Some("off") | None => None,
+                Some(name) => Some(Width::named(name).ok_or(format!(
+                    "Invalid integer type '{}': Expected off or one of i8, u8, i16, u16, i32, u32, i64, u64, i128 or u128",
+                    name
+                ))?),
+            };
+            let mut settings = environment::fetch_settings().lock().unwrap();
+            settings.width = width;
+            settings.modulus = None;
+            Ok(())
+        }
+        Some("round") => {
+            let ties = match args.next() {
+                Some(name) => Ties::named(name).ok_or(format!(
+                    "Invalid tie-breaking rule '{}': Expected away, even, zero, up or down",
+                    name
+                ))?,
+                None => Ties::Away,
+            };
+            environment::fetch_settings().lock().unwrap().ties = ties;
+            Ok(())
+        }
+        Some("mod") => {
+            let expr = args.collect::<Vec<_>>().join(" ");
+            // The new modulus isn't reduced by the old one, which stays if it's invalid.
+            let modulus = match expr.as_str() {
+                "" | "off" => None,
+                _ => Some(
+                    integer::unreduced(|| eval::evaluate(&expr))?
+                        .integer()
+                        .map(|n| n.value)
+                        .ok()
+                        .filter(|n| *n >= BigInt::from(2))
+                        .ok_or(format!(
+                            "Invalid modulus '{}': Expected off or a whole number of at least 2",
+                            expr
+                        ))?,
+                ),
+            };
+
+            let mut settings = environment::fetch_settings().lock().unwrap();
+            settings.modulus = modulus;
+            settings.width = None;
+            Ok(())
+        }
+        Some("seed") => {
+            let seed = match args.next() {
+                Some("off") | None => None,
+                Some(seed) => Some(seed.parse().map_err(|_| {
+                    format!(
+                        "Invalid seed '{}': Expected off or a whole number between 0 and {}",
+                        seed,
+                        u64::MAX
+                    )
+                })?),
+            };
+            random::seed(seed);
+            Ok(())
+        }
+        Some(name) => Err(format!(
+            "Unknown command ':{}': Expected one of ':base', ':int', ':load', ':mod', ':rates', ':round' or ':seed'",
+            name
+        )),
+        None => Err("Missing command: Expected a command name after ':'".into()),
+    }
+}
 
-        match eval::evaluate(input.trim()) {
-            Ok(n) => println!("=> {}", n),
-            Err(e) => eprintln!("=> {}", e),
+// Runs every line of a file, skipping blank lines and lines starting with '#'.
+fn load(path: &str, echo: bool) -> Result<(), String> {
+    let source =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
         }
+
+        execute(line, echo).map_err(|e| format!("{}:{}: {}", path, number + 1, e))?;
     }
+
+    Ok(())
 }
@@ -1,11 +1,55 @@
-pub mod executor;
+pub mod calculus;
+pub mod currency;
+pub mod date;
+pub mod distribution;
 pub mod environment;
+pub mod executor;
+pub mod float;
+pub mod integer;
 pub mod lexer;
+pub mod matrix;
 pub mod parser;
+pub mod polynomial;
+pub mod radix;
+pub mod random;
+pub mod roots;
+pub mod rounding;
+pub mod special;
+pub mod stats;
 pub mod types;
+pub mod unit;
+
+pub fn evaluate(expr: &str) -> Result<types::Value, String> {
+    executor::calculate(&parser::parse(lexer::lex(&mut expr.chars().peekable())?)?)
+        .map(integer::residues)
+}
+
+// Settings and variables are shared by the whole session, so tests that evaluate expressions
+// take turns.
+#[cfg(test)]
+pub mod session {
+    use std::sync::Mutex;
+
+    use num_bigint::BigInt;
+
+    use crate::eval::{environment, evaluate};
+
+    static SESSION: Mutex<()> = Mutex::new(());
+
+    fn show(expr: &str) -> String {
+        evaluate(expr).map_or_else(|e| e, |value| value.to_string())
+    }
+
+    pub fn eval(expr: &str) -> String {
+        let _session = SESSION.lock().unwrap_or_else(|e| e.into_inner());
+        show(expr)
+    }
 
-pub fn evaluate(expr: &str) -> Result<f64, String> {
-    Ok(executor::calculate(
-        parser::parse(lexer::lex(&mut expr.chars().peekable())?)?,
-    ))
+    pub fn modulo(modulus: u32, exprs: &[&str]) -> Vec<String> {
+        let _session = SESSION.lock().unwrap_or_else(|e| e.into_inner());
+        environment::fetch_settings().lock().unwrap().modulus = Some(BigInt::from(modulus));
+        let results = exprs.iter().map(|expr| show(expr)).collect();
+        environment::fetch_settings().lock().unwrap().modulus = None;
+        results
+    }
 }
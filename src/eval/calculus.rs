@@ -0,0 +1,284 @@
+use crate::eval::unit::Quantity;
+
+// How much smaller each step of a derivative gets, and how many steps are tried at most.
+const SHRINK: f64 = 1.4;
+const STEPS: usize = 10;
+
+// The most pieces an integral is cut into before giving up on it.
+const PIECES: usize = 2000;
+
+// Nodes and weights of the 15-point Kronrod rule from the middle of an interval outwards, the
+// last being the middle itself, and of the 7-point Gauss rule that uses every other node.
+const NODES: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_5,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_48,
+    0.0,
+];
+const KRONROD: [f64; 8] = [
+    0.022_935_322_010_529_224,
+    0.063_092_092_629_978_56,
+    0.104_790_010_322_250_19,
+    0.140_653_259_715_525_92,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_42,
+    0.204_432_940_075_298_89,
+    0.209_482_141_084_727_82,
+];
+const GAUSS: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_64,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+// The derivative at x by Ridders' method: central differences over shrinking steps, combined
+// by Richardson extrapolation into ever higher orders, keeping whichever estimate changed least.
+pub fn derivative(f: impl Fn(f64) -> Result<f64, String>, x: f64) -> Result<f64, String> {
+    let central = |h: f64| Ok::<_, String>((f(x + h)? - f(x - h)?) / (2.0 * h));
+
+    // Steps start at a tenth of x, and any that leave where the function is defined, as for
+    // sqrt near 0, are shortened.
+    let mut h = match x {
+        0.0 => 0.1,
+        x => 0.1 * x.abs(),
+    };
+    let mut first = central(h)?;
+    for _ in 0..50 {
+        if first.is_finite() {
+            break;
+        }
+        h /= 2.0;
+        first = central(h)?;
+    }
+    if !first.is_finite() {
+        return Err(format!(
+            "Not differentiable: The function isn't finite around {}",
+            Quantity::from(x)
+        ));
+    }
+
+    let mut table = vec![vec![first]];
+    let (mut best, mut error) = (first, f64::INFINITY);
+    for i in 1..STEPS {
+        h /= SHRINK;
+        let mut row = vec![central(h)?];
+        let mut factor = SHRINK * SHRINK;
+        for j in 1..=i {
+            let next = (row[j - 1] * factor - table[i - 1][j - 1]) / (factor - 1.0);
+            factor *= SHRINK * SHRINK;
+            let change = (next - row[j - 1])
+                .abs()
+                .max((next - table[i - 1][j - 1]).abs());
+            if change <= error {
+                (best, error) = (next, change);
+            }
+            row.push(next);
+        }
+        // Once the highest order gets worse, rounding errors have taken over.
+        if (row[i] - table[i - 1][i - 1]).abs() >= 2.0 * error {
+            break;
+        }
+        table.push(row);
+    }
+
+    // Differences can't be trusted beyond the rounding errors in the values they subtract and
+    // in the points themselves, which extrapolating magnifies.
+    let sizes = f(x + h)?.abs() + f(x - h)?.abs() + x.abs() * best.abs();
+    let noise = 16.0 * f64::EPSILON * sizes / (2.0 * h);
+    if noise.is_finite() {
+        error = error.max(noise);
+    }
+    if error.is_nan() || error > 1e-6 * best.abs().max(1.0) {
+        return Err(format!(
+            "No convergence: 'deriv' only reached an estimated error of {}",
+            Quantity::from(error)
+        ));
+    }
+    Ok(round(best, error))
+}
+
+// The integral from a to b. Infinite bounds are brought in by a change of variable onto a
+// finite interval, whose ends the Kronrod nodes never reach.
+pub fn integral(f: impl Fn(f64) -> Result<f64, String>, a: f64, b: f64) -> Result<f64, String> {
+    if a.is_nan() || b.is_nan() {
+        return Err("Invalid bounds: 'integrate' needs bounds that are numbers".into());
+    }
+    if a == b {
+        return Ok(0.0);
+    }
+    if a > b {
+        return integral(f, b, a).map(|value| 0.0 - value);
+    }
+
+    // Where the function isn't finite is shown to the precision of the bounds, since it's
+    // usually found just inside one of them.
+    let scale = [a, b]
+        .into_iter()
+        .filter(|x| x.is_finite())
+        .fold(0.0, |scale: f64, x| scale.max(x.abs()));
+    let f = |x: f64| match f(x)? {
+        y if y.is_finite() => Ok(y),
+        _ => Err(format!(
+            "Not integrable: The function isn't finite at {}",
+            Quantity::from(round(x, 1e-12 * scale.max(x.abs())))
+        )),
+    };
+    // Points so close to the end of the interval that they're mapped to infinity count as
+    // zero, which is where the function must go for the integral to converge.
+    let at = |x: f64, dx: f64| match x.is_infinite() {
+        true => Ok(0.0),
+        false => Ok(f(x)? * dx),
+    };
+    match (a.is_infinite(), b.is_infinite()) {
+        (false, false) => adaptive(&f, a, b),
+        (false, true) => adaptive(&|t| at(a + t / (1.0 - t), (1.0 - t).powi(-2)), 0.0, 1.0),
+        (true, false) => adaptive(&|t| at(b - t / (1.0 - t), (1.0 - t).powi(-2)), 0.0, 1.0),
+        (true, true) => adaptive(
+            &|t| at(t / (1.0 - t * t), (1.0 + t * t) / (1.0 - t * t).powi(2)),
+            -1.0,
+            1.0,
+        ),
+    }
+}
+
+struct Piece {
+    a: f64,
+    b: f64,
+    value: f64,
+    error: f64,
+    magnitude: f64,
+}
+
+// Splits whichever piece has the largest estimated error in two, until the errors add up to
+// a small enough share of the integral, or to what rounding alone would leave.
+fn adaptive(f: &dyn Fn(f64) -> Result<f64, String>, a: f64, b: f64) -> Result<f64, String> {
+    let mut pieces = vec![kronrod(f, a, b)?];
+    loop {
+        let value: f64 = pieces.iter().map(|piece| piece.value).sum();
+        let error: f64 = pieces.iter().map(|piece| piece.error).sum();
+        let magnitude: f64 = pieces.iter().map(|piece| piece.magnitude).sum();
+        if error <= (1e-12 * value.abs()).max(100.0 * f64::EPSILON * magnitude) {
+            return Ok(round(value, error));
+        }
+
+        let worst = (0..pieces.len())
+            .max_by(|&i, &j| pieces[i].error.total_cmp(&pieces[j].error))
+            .unwrap();
+        let piece = pieces.swap_remove(worst);
+        let middle = (piece.a + piece.b) / 2.0;
+        if pieces.len() + 2 > PIECES || middle <= piece.a || middle >= piece.b {
+            return Err(format!(
+                "No convergence: 'integrate' only reached an estimated error of {}, so the integral may diverge",
+                Quantity::from(error)
+            ));
+        }
+        pieces.push(kronrod(f, piece.a, middle)?);
+        pieces.push(kronrod(f, middle, piece.b)?);
+    }
+}
+
+// The 15-point Kronrod estimate over one piece, with the error estimated from how far the
+// 7-point Gauss estimate is from it, scaled as QUADPACK does.
+fn kronrod(f: &dyn Fn(f64) -> Result<f64, String>, a: f64, b: f64) -> Result<Piece, String> {
+    let center = (a + b) / 2.0;
+    let half = (b - a) / 2.0;
+
+    let mut ys = vec![(f(center)?, KRONROD[7])];
+    let mut gauss = ys[0].0 * GAUSS[3];
+    for j in 0..7 {
+        let dx = half * NODES[j];
+        let (left, right) = (f(center - dx)?, f(center + dx)?);
+        ys.push((left, KRONROD[j]));
+        ys.push((right, KRONROD[j]));
+        if j % 2 == 1 {
+            gauss += GAUSS[j / 2] * (left + right);
+        }
+    }
+
+    let kronrod: f64 = ys.iter().map(|(y, w)| w * y).sum();
+    let mean = kronrod / 2.0;
+    let magnitude = half * ys.iter().map(|(y, w)| w * y.abs()).sum::<f64>();
+    let spread = half * ys.iter().map(|(y, w)| w * (y - mean).abs()).sum::<f64>();
+
+    let mut error = (half * (kronrod - gauss)).abs();
+    if spread != 0.0 && error != 0.0 {
+        error = spread * (200.0 * error / spread).powf(1.5).min(1.0);
+    }
+    if magnitude > f64::MIN_POSITIVE / (50.0 * f64::EPSILON) {
+        error = error.max(50.0 * f64::EPSILON * magnitude);
+    }
+    Ok(Piece {
+        a,
+        b,
+        value: half * kronrod,
+        error,
+        magnitude,
+    })
+}
+
+// Digits finer than the estimated error are noise, so the result is rounded to it.
+fn round(value: f64, error: f64) -> f64 {
+    if error.is_nan() || error <= 0.0 || !value.is_finite() {
+        return value;
+    }
+    let digits = -error.log10().floor() as i32;
+    let rounded = match digits >= 0 {
+        true => (value * 10_f64.powi(digits)).round() / 10_f64.powi(digits),
+        false => (value / 10_f64.powi(-digits)).round() * 10_f64.powi(-digits),
+    };
+    match rounded.is_finite() {
+        true => rounded + 0.0,
+        false => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{derivative, integral};
+    use std::f64::consts::PI;
+
+    fn close(value: f64, expected: f64) -> bool {
+        (value - expected).abs() <= 1e-10 * expected.abs().max(1.0)
+    }
+
+    #[test]
+    fn derivatives_of_known_functions() {
+        assert!(close(derivative(|x| Ok(x.sin()), 0.0).unwrap(), 1.0));
+        assert!(close(
+            derivative(|x| Ok(x.exp()), 1.0).unwrap(),
+            1_f64.exp()
+        ));
+        assert!(close(derivative(|x| Ok(x * x * x), 2.0).unwrap(), 12.0));
+        // sqrt isn't defined left of 0, so the steps around 0.01 have to shrink to fit.
+        assert!(close(derivative(|x| Ok(x.sqrt()), 0.01).unwrap(), 5.0));
+    }
+
+    #[test]
+    fn derivative_at_a_pole_fails() {
+        assert!(derivative(|x| Ok(1.0 / x), 0.0).is_err());
+    }
+
+    #[test]
+    fn integrals_of_known_functions() {
+        assert!(close(integral(|x| Ok(x * x), 0.0, 3.0).unwrap(), 9.0));
+        assert!(close(integral(|x| Ok(x.sin()), 0.0, PI).unwrap(), 2.0));
+        // The Gaussian integral, over the whole line and over half of it.
+        let gaussian = |x: f64| Ok((-x * x).exp());
+        let (a, b) = (f64::NEG_INFINITY, f64::INFINITY);
+        assert!(close(integral(gaussian, a, b).unwrap(), PI.sqrt()));
+        assert!(close(integral(gaussian, 0.0, b).unwrap(), PI.sqrt() / 2.0));
+        assert!(close(integral(|x| Ok(1.0 / (x * x)), 1.0, b).unwrap(), 1.0));
+    }
+
+    #[test]
+    fn integral_bounds_in_either_order() {
+        assert!(close(integral(Ok, 2.0, 0.0).unwrap(), -2.0));
+        assert_eq!(integral(Ok, 1.0, 1.0).unwrap(), 0.0);
+        assert!(integral(Ok, f64::NAN, 1.0).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use std::{fs, path::Path, time::UNIX_EPOCH};
+
+use crate::eval::{
+    date::Date,
+    environment,
+    unit::{Dimension, Prefix, Unit},
+};
+
+pub mod json;
+
+// The base currency, if the file names one, and each currency's rate against it.
+pub type Table = (Option<String>, Vec<(String, f64)>);
+
+#[derive(Debug, Clone)]
+pub struct Rates {
+    pub path: String,
+    pub base: String,
+    pub modified: u64,
+}
+
+// Each rate is the amount of that currency worth one unit of the base currency.
+pub fn load(path: &str) -> Result<usize, String> {
+    let source =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_secs());
+
+    let (base, rates) = match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("json") => json::rates(&source)?,
+        _ => csv(&source)?,
+    };
+
+    let base = match base {
+        Some(base) => base,
+        None => rates
+            .iter()
+            .find(|(_, rate)| *rate == 1.0)
+            .map(|(code, _)| code.clone())
+            .ok_or_else(|| {
+                format!(
+                    "Missing base currency in '{}': Expected a currency with a rate of 1",
+                    path
+                )
+            })?,
+    };
+
+    if let Some((code, rate)) = rates.iter().find(|(_, rate)| *rate <= 0.0) {
+        return Err(format!(
+            "Invalid rate for '{}': Expected a positive number but got {}",
+            code, rate
+        ));
+    }
+
+    let mut units = environment::fetch_units().lock().unwrap();
+    let mut current = environment::fetch_rates().lock().unwrap();
+    if let Some(old) = current.take() {
+        let dim = Dimension::from(&[(old.base.as_str(), 1)][..]);
+        units.retain(|unit| unit.dim != dim);
+    }
+
+    let dim = [(base.as_str(), 1)];
+    units.push(Unit::new(&base, 1.0, &dim, Prefix::None));
+    for (code, rate) in rates.iter() {
+        if *code != base {
+            units.push(Unit::new(code, 1.0 / rate, &dim, Prefix::None));
+        }
+    }
+
+    *current = Some(Rates {
+        path: path.to_string(),
+        base,
+        modified,
+    });
+    Ok(rates.len())
+}
+
+pub fn reload() -> Result<usize, String> {
+    let path = environment::fetch_rates()
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|rates| rates.path.clone())
+        .ok_or("No rates loaded: Expected a file path after ':rates'")?;
+
+    load(&path)
+}
+
+// Shown next to results in a currency so that stale rates are noticed.
+pub fn note(dim: &Dimension) -> Option<String> {
+    let rates = environment::fetch_rates().lock().unwrap();
+    let rates = rates.as_ref()?;
+    if !dim.contains(&rates.base) {
+        return None;
+    }
+
+    Some(format!(
+        "(rates from {})",
+        Date::new(rates.modified as f64, 0)
+    ))
+}
+
+fn csv(source: &str) -> Result<Table, String> {
+    let mut rates = Vec::new();
+    for line in source.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((code, rate)) = line.split_once(',') else {
+            return Err(format!(
+                "Invalid rates line '{}': Expected 'CODE,rate'",
+                line
+            ));
+        };
+
+        // A header row such as "currency,rate" is skipped.
+        match rate.trim().parse::<f64>() {
+            Ok(rate) => rates.push((code.trim().to_string(), rate)),
+            Err(_) if rates.is_empty() => continue,
+            Err(_) => {
+                return Err(format!(
+                    "Invalid rate '{}': Expected a number for '{}'",
+                    rate.trim(),
+                    code.trim()
+                ));
+            }
+        }
+    }
+
+    Ok((None, rates))
+}
@@ -0,0 +1,141 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::eval::currency::Table;
+
+enum Value {
+    Object(Vec<(String, Value)>),
+    Str(String),
+    Num(f64),
+    Other,
+}
+
+// Reads files shaped like {"base": "EUR", "rates": {"USD": 1.08, ...}}, ignoring any other keys.
+pub fn rates(source: &str) -> Result<Table, String> {
+    let Value::Object(fields) = value(&mut source.chars().peekable())? else {
+        return Err("Invalid rates file: Expected a JSON object at the top level".into());
+    };
+
+    let mut base = None;
+    let mut rates = Vec::new();
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("base", Value::Str(code)) => base = Some(code),
+            ("rates", Value::Object(entries)) => {
+                for (code, rate) in entries {
+                    match rate {
+                        Value::Num(rate) => rates.push((code, rate)),
+                        _ => {
+                            return Err(format!("Invalid rate for '{}': Expected a number", code));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if rates.is_empty() {
+        return Err("Invalid rates file: Expected a non-empty \"rates\" object".into());
+    }
+
+    if let Some(code) = &base
+        && !rates.iter().any(|(c, _)| c == code)
+    {
+        rates.push((code.clone(), 1.0));
+    }
+
+    Ok((base, rates))
+}
+
+fn value(chars: &mut Peekable<Chars>) -> Result<Value, String> {
+    skip(chars);
+    match chars.peek() {
+        Some('{') => {
+            chars.next();
+            let mut fields = Vec::new();
+            loop {
+                skip(chars);
+                match chars.next() {
+                    Some('}') => break,
+                    Some(',') => continue,
+                    Some('"') => {
+                        let key = string(chars)?;
+                        skip(chars);
+                        if chars.next() != Some(':') {
+                            return Err(format!("Invalid JSON: Expected ':' after \"{}\"", key));
+                        }
+                        fields.push((key, value(chars)?));
+                    }
+                    _ => return Err("Invalid JSON: Expected a key or '}' in object".into()),
+                }
+            }
+            Ok(Value::Object(fields))
+        }
+        Some('[') => {
+            chars.next();
+            loop {
+                skip(chars);
+                match chars.peek() {
+                    Some(']') => {
+                        chars.next();
+                        break;
+                    }
+                    Some(',') => {
+                        chars.next();
+                    }
+                    Some(_) => {
+                        value(chars)?;
+                    }
+                    None => return Err("Invalid JSON: Unclosed '['".into()),
+                }
+            }
+            Ok(Value::Other)
+        }
+        Some('"') => {
+            chars.next();
+            Ok(Value::Str(string(chars)?))
+        }
+        Some(_) => {
+            let mut literal = String::new();
+            while let Some(c) = chars.peek() {
+                if matches!(c, ',' | '}' | ']') || c.is_whitespace() {
+                    break;
+                }
+                literal.push(*c);
+                chars.next();
+            }
+
+            match literal.as_str() {
+                "true" | "false" | "null" => Ok(Value::Other),
+                _ => literal
+                    .parse()
+                    .map(Value::Num)
+                    .map_err(|_| format!("Invalid JSON: Unexpected '{}'", literal)),
+            }
+        }
+        None => Err("Invalid JSON: Unexpected end of file".into()),
+    }
+}
+
+fn string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    let mut s = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Ok(s),
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    s.push(c);
+                }
+            }
+            _ => s.push(c),
+        }
+    }
+
+    Err("Invalid JSON: Unclosed string".into())
+}
+
+fn skip(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
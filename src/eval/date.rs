@@ -0,0 +1,148 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::eval::unit::{Dimension, Quantity};
+
+// A point in time stored as seconds since the Unix epoch in UTC, along with the UTC offset
+// in seconds that it is displayed and split into calendar fields with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Date {
+    pub secs: f64,
+    pub offset: i64,
+}
+
+impl Date {
+    pub fn new(secs: f64, offset: i64) -> Self {
+        Date { secs, offset }
+    }
+
+    pub fn from_civil(year: i64, month: i64, day: i64, time: f64, offset: i64) -> Option<Self> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        let days = days_from_civil(year, month, day);
+        Some(Date::new(
+            days as f64 * 86400.0 + time - offset as f64,
+            offset,
+        ))
+    }
+
+    pub fn now() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |duration| duration.as_secs_f64());
+        Date::new(secs, 0)
+    }
+
+    pub fn add(self, duration: Quantity) -> Result<Self, String> {
+        duration.compatible(&seconds(0.0), "add")?;
+        Ok(Date::new(self.secs + duration.value, self.offset))
+    }
+
+    pub fn with_offset(self, offset: Quantity) -> Result<Self, String> {
+        offset.compatible(&seconds(0.0), "convert")?;
+        if offset.value.abs() > 18.0 * 3600.0 || offset.value.fract() != 0.0 {
+            return Err(format!(
+                "Invalid UTC offset '{}': Expected whole seconds between -18:00 and +18:00",
+                offset
+            ));
+        }
+
+        Ok(Date::new(self.secs, offset.value as i64))
+    }
+
+    // Days since the epoch and seconds into that day, both in the date's own offset.
+    fn local(&self) -> (i64, f64) {
+        let local = self.secs + self.offset as f64;
+        let days = (local / 86400.0).floor();
+        (days as i64, local - days * 86400.0)
+    }
+
+    // ISO weekday, where Monday is 1 and Sunday is 7.
+    pub fn weekday(&self) -> i64 {
+        let (days, _) = self.local();
+        (days + 3).rem_euclid(7) + 1
+    }
+
+    pub fn isoweek(&self) -> i64 {
+        let (days, _) = self.local();
+        let thursday = days + 4 - self.weekday();
+        let (year, _, _) = civil_from_days(thursday);
+        (thursday - days_from_civil(year, 1, 1)) / 7 + 1
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (days, time) = self.local();
+        let (year, month, day) = civil_from_days(days);
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)?;
+
+        if time == 0.0 && self.offset == 0 {
+            return Ok(());
+        }
+
+        let whole = time.floor() as i64;
+        write!(
+            f,
+            " {:02}:{:02}:{:02}",
+            whole / 3600,
+            whole % 3600 / 60,
+            whole % 60
+        )?;
+
+        let fraction = format!("{:.3}", time.fract());
+        if fraction != "0.000" {
+            write!(
+                f,
+                "{}",
+                fraction.trim_start_matches('0').trim_end_matches('0')
+            )?;
+        }
+
+        if self.offset == 0 {
+            return write!(f, " UTC");
+        }
+
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let offset = self.offset.abs();
+        write!(f, " {}{:02}:{:02}", sign, offset / 3600, offset % 3600 / 60)
+    }
+}
+
+pub fn seconds(value: f64) -> Quantity {
+    Quantity::new(value, Dimension::from(&[("s", 1)][..]))
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Conversions between civil dates and days since 1970-01-01, see
+// http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
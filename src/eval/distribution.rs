@@ -0,0 +1,200 @@
+use std::f64::consts::{PI, SQRT_2};
+
+use crate::eval::special;
+
+fn check(condition: bool, id: &str, expected: &str) -> Result<(), String> {
+    match condition {
+        true => Ok(()),
+        false => Err(format!(
+            "Invalid arguments for '{}': Expected {}",
+            id, expected
+        )),
+    }
+}
+
+fn whole(id: &str, name: &str, n: f64) -> Result<f64, String> {
+    check(
+        n >= 0.0 && n.fract() == 0.0,
+        id,
+        &format!("{} to be a whole number of at least 0", name),
+    )?;
+    Ok(n)
+}
+
+pub fn normpdf(x: f64, mu: f64, sigma: f64) -> Result<f64, String> {
+    check(sigma > 0.0, "normpdf", "a positive standard deviation")?;
+    let z = (x - mu) / sigma;
+    Ok((-z * z / 2.0).exp() / (sigma * (2.0 * PI).sqrt()))
+}
+
+// Written with erfc so that far tails like normcdf(-10) don't cancel to zero.
+pub fn normcdf(x: f64, mu: f64, sigma: f64) -> Result<f64, String> {
+    check(sigma > 0.0, "normcdf", "a positive standard deviation")?;
+    Ok(0.5 * special::erfc(-(x - mu) / (sigma * SQRT_2)))
+}
+
+// Acklam's rational approximation, polished with a step of Halley's method, see
+// https://web.archive.org/web/20151030215612/http://home.online.no/~pjacklam/notes/invnorm/
+pub fn norminv(p: f64, mu: f64, sigma: f64) -> Result<f64, String> {
+    check(sigma > 0.0, "norminv", "a positive standard deviation")?;
+    check(
+        (0.0..=1.0).contains(&p),
+        "norminv",
+        "a probability between 0 and 1",
+    )?;
+    if p == 0.0 {
+        return Ok(f64::NEG_INFINITY);
+    }
+    if p == 1.0 {
+        return Ok(f64::INFINITY);
+    }
+
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    let low = 0.02425;
+    let mut x = if p < low {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - low {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    };
+
+    let e = 0.5 * special::erfc(-x / SQRT_2) - p;
+    let u = e * (2.0 * PI).sqrt() * (x * x / 2.0).exp();
+    x -= u / (1.0 + x * u / 2.0);
+
+    Ok(mu + sigma * x)
+}
+
+pub fn binompdf(n: f64, p: f64, k: f64) -> Result<f64, String> {
+    let n = whole("binompdf", "n", n)?;
+    check(
+        (0.0..=1.0).contains(&p),
+        "binompdf",
+        "a probability between 0 and 1",
+    )?;
+    if k < 0.0 || k > n || k.fract() != 0.0 {
+        return Ok(0.0);
+    }
+    if p == 0.0 || p == 1.0 {
+        return Ok(if (p == 0.0 && k == 0.0) || (p == 1.0 && k == n) {
+            1.0
+        } else {
+            0.0
+        });
+    }
+
+    // The coefficient is exact through gamma until it would overflow.
+    if n < 171.0 {
+        let choose =
+            special::gamma(n + 1.0)? / (special::gamma(k + 1.0)? * special::gamma(n - k + 1.0)?);
+        return Ok(choose * p.powf(k) * (1.0 - p).powf(n - k));
+    }
+
+    let ln_choose =
+        special::lgamma(n + 1.0)? - special::lgamma(k + 1.0)? - special::lgamma(n - k + 1.0)?;
+    Ok((ln_choose + k * p.ln() + (n - k) * (1.0 - p).ln()).exp())
+}
+
+// P(X <= k) = I_(1-p)(n - k, k + 1)
+pub fn binomcdf(n: f64, p: f64, k: f64) -> Result<f64, String> {
+    let n = whole("binomcdf", "n", n)?;
+    check(
+        (0.0..=1.0).contains(&p),
+        "binomcdf",
+        "a probability between 0 and 1",
+    )?;
+    let k = k.floor();
+    if k < 0.0 {
+        return Ok(0.0);
+    }
+    if k >= n {
+        return Ok(1.0);
+    }
+    special::incomplete_beta(n - k, k + 1.0, 1.0 - p)
+}
+
+pub fn poissonpdf(lambda: f64, k: f64) -> Result<f64, String> {
+    check(lambda > 0.0, "poissonpdf", "a positive mean")?;
+    if k < 0.0 || k.fract() != 0.0 {
+        return Ok(0.0);
+    }
+    Ok((k * lambda.ln() - lambda - special::lgamma(k + 1.0)?).exp())
+}
+
+// P(X <= k) = Q(k + 1, lambda)
+pub fn poissoncdf(lambda: f64, k: f64) -> Result<f64, String> {
+    check(lambda > 0.0, "poissoncdf", "a positive mean")?;
+    let k = k.floor();
+    if k < 0.0 {
+        return Ok(0.0);
+    }
+    Ok(special::incomplete_gamma(k + 1.0, lambda)?.1)
+}
+
+// Student's t distribution, through the incomplete beta function of df / (df + t^2).
+pub fn tcdf(t: f64, df: f64) -> Result<f64, String> {
+    check(df > 0.0, "tcdf", "positive degrees of freedom")?;
+    if t.is_infinite() {
+        return Ok(if t > 0.0 { 1.0 } else { 0.0 });
+    }
+    let tail = 0.5 * special::incomplete_beta(df / 2.0, 0.5, df / (df + t * t))?;
+    Ok(if t > 0.0 { 1.0 - tail } else { tail })
+}
+
+pub fn chi2cdf(x: f64, df: f64) -> Result<f64, String> {
+    check(df > 0.0, "chi2cdf", "positive degrees of freedom")?;
+    if x <= 0.0 {
+        return Ok(0.0);
+    }
+    Ok(special::incomplete_gamma(df / 2.0, x / 2.0)?.0)
+}
+
+pub fn expcdf(x: f64, lambda: f64) -> Result<f64, String> {
+    check(lambda > 0.0, "expcdf", "a positive rate")?;
+    if x <= 0.0 {
+        return Ok(0.0);
+    }
+    Ok(-(-lambda * x).exp_m1())
+}
+
+pub fn unifcdf(x: f64, a: f64, b: f64) -> Result<f64, String> {
+    check(a < b, "unifcdf", "a lower bound below the upper bound")?;
+    Ok(((x - a) / (b - a)).clamp(0.0, 1.0))
+}
@@ -1,10 +1,35 @@
-use crate::eval::types::Expr;
+use num_bigint::BigInt;
+
+use crate::eval::{
+    currency::Rates,
+    integer::Width,
+    random::Rng,
+    rounding::Ties,
+    types::{Expr, Operator, Value},
+    unit::{Prefix, Unit},
+};
 use std::{
     f64::consts,
     sync::{Mutex, OnceLock},
 };
 
 pub static VARIABLES: OnceLock<Mutex<Vec<Expr>>> = OnceLock::new();
+pub static UNITS: OnceLock<Mutex<Vec<Unit>>> = OnceLock::new();
+pub static RATES: OnceLock<Mutex<Option<Rates>>> = OnceLock::new();
+pub static SETTINGS: OnceLock<Mutex<Settings>> = OnceLock::new();
+pub static RANDOM: OnceLock<Mutex<Rng>> = OnceLock::new();
+// Parameters of the lambdas being parsed, innermost last.
+pub static PARAMETERS: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+// Values of the parameters of the lambdas being called, innermost last.
+pub static BINDINGS: OnceLock<Mutex<Vec<(String, Value)>>> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub base: u32,
+    pub width: Option<Width>,
+    pub modulus: Option<BigInt>,
+    pub ties: Ties,
+}
 
 pub fn fetch_variables() -> &'static Mutex<Vec<Expr>> {
     VARIABLES.get_or_init(|| {
@@ -15,7 +40,140 @@ pub fn fetch_variables() -> &'static Mutex<Vec<Expr>> {
                 "phi".to_string(),
                 Box::new(Expr::Num((1.0 + 5.0_f64.sqrt()) / 2.0)),
             ),
+            Expr::Var("inf".to_string(), Box::new(Expr::Num(f64::INFINITY))),
+            // Zero offset, for showing dates in UTC with `to UTC`.
+            Expr::Var(
+                "UTC".to_string(),
+                Box::new(Expr::Bin(
+                    Box::new(Expr::Num(0.0)),
+                    Operator::Multiplication,
+                    Box::new(Expr::Unit("s".to_string())),
+                )),
+            ),
         ])
     })
 }
 
+pub fn fetch_units() -> &'static Mutex<Vec<Unit>> {
+    UNITS.get_or_init(|| {
+        const M: Prefix = Prefix::Metric;
+        const B: Prefix = Prefix::Binary;
+        const N: Prefix = Prefix::None;
+
+        Mutex::new(vec![
+            // SI base units, with the kilogram defined through the gram so it takes prefixes.
+            Unit::new("m", 1.0, &[("m", 1)], M),
+            Unit::new("g", 1e-3, &[("kg", 1)], M),
+            Unit::new("s", 1.0, &[("s", 1)], M),
+            Unit::new("A", 1.0, &[("A", 1)], M),
+            Unit::new("K", 1.0, &[("K", 1)], M),
+            Unit::affine("°C", 1.0, 273.15, &[("K", 1)], N),
+            Unit::affine("degC", 1.0, 273.15, &[("K", 1)], N),
+            Unit::affine("°F", 5.0 / 9.0, 273.15 - 32.0 * 5.0 / 9.0, &[("K", 1)], N),
+            Unit::affine("degF", 5.0 / 9.0, 273.15 - 32.0 * 5.0 / 9.0, &[("K", 1)], N),
+            Unit::new("°R", 5.0 / 9.0, &[("K", 1)], N),
+            Unit::new("mol", 1.0, &[("mol", 1)], M),
+            Unit::new("cd", 1.0, &[("cd", 1)], M),
+            Unit::new("bit", 1.0, &[("bit", 1)], B),
+            // SI derived units.
+            Unit::new("rad", 1.0, &[], M),
+            Unit::new("sr", 1.0, &[], M),
+            Unit::new("Hz", 1.0, &[("s", -1)], M),
+            Unit::new("N", 1.0, &[("kg", 1), ("m", 1), ("s", -2)], M),
+            Unit::new("Pa", 1.0, &[("kg", 1), ("m", -1), ("s", -2)], M),
+            Unit::new("J", 1.0, &[("kg", 1), ("m", 2), ("s", -2)], M),
+            Unit::new("W", 1.0, &[("kg", 1), ("m", 2), ("s", -3)], M),
+            Unit::new("C", 1.0, &[("A", 1), ("s", 1)], M),
+            Unit::new("V", 1.0, &[("kg", 1), ("m", 2), ("s", -3), ("A", -1)], M),
+            Unit::new("F", 1.0, &[("kg", -1), ("m", -2), ("s", 4), ("A", 2)], M),
+            Unit::new("Ω", 1.0, &[("kg", 1), ("m", 2), ("s", -3), ("A", -2)], M),
+            Unit::new("ohm", 1.0, &[("kg", 1), ("m", 2), ("s", -3), ("A", -2)], M),
+            Unit::new("S", 1.0, &[("kg", -1), ("m", -2), ("s", 3), ("A", 2)], M),
+            Unit::new("Wb", 1.0, &[("kg", 1), ("m", 2), ("s", -2), ("A", -1)], M),
+            Unit::new("T", 1.0, &[("kg", 1), ("s", -2), ("A", -1)], M),
+            Unit::new("H", 1.0, &[("kg", 1), ("m", 2), ("s", -2), ("A", -2)], M),
+            Unit::new("lm", 1.0, &[("cd", 1)], M),
+            Unit::new("lx", 1.0, &[("cd", 1), ("m", -2)], M),
+            Unit::new("Bq", 1.0, &[("s", -1)], M),
+            Unit::new("Gy", 1.0, &[("m", 2), ("s", -2)], M),
+            Unit::new("Sv", 1.0, &[("m", 2), ("s", -2)], M),
+            Unit::new("kat", 1.0, &[("mol", 1), ("s", -1)], M),
+            // Units accepted alongside the SI.
+            Unit::new("L", 1e-3, &[("m", 3)], M),
+            Unit::new("l", 1e-3, &[("m", 3)], M),
+            Unit::new("t", 1e3, &[("kg", 1)], M),
+            Unit::new("eV", 1.602176634e-19, &[("kg", 1), ("m", 2), ("s", -2)], M),
+            Unit::new("Wh", 3600.0, &[("kg", 1), ("m", 2), ("s", -2)], M),
+            Unit::new("bar", 1e5, &[("kg", 1), ("m", -1), ("s", -2)], M),
+            Unit::new("B", 8.0, &[("bit", 1)], B),
+            Unit::new("min", 60.0, &[("s", 1)], N),
+            Unit::new("h", 3600.0, &[("s", 1)], N),
+            Unit::new("d", 86400.0, &[("s", 1)], N),
+            Unit::new("wk", 604800.0, &[("s", 1)], N),
+            Unit::new("yr", 31557600.0, &[("s", 1)], N),
+            Unit::new("second", 1.0, &[("s", 1)], N),
+            Unit::new("seconds", 1.0, &[("s", 1)], N),
+            Unit::new("minute", 60.0, &[("s", 1)], N),
+            Unit::new("minutes", 60.0, &[("s", 1)], N),
+            Unit::new("hour", 3600.0, &[("s", 1)], N),
+            Unit::new("hours", 3600.0, &[("s", 1)], N),
+            Unit::new("day", 86400.0, &[("s", 1)], N),
+            Unit::new("days", 86400.0, &[("s", 1)], N),
+            Unit::new("week", 604800.0, &[("s", 1)], N),
+            Unit::new("weeks", 604800.0, &[("s", 1)], N),
+            Unit::new("deg", consts::PI / 180.0, &[], N),
+            Unit::new("°", consts::PI / 180.0, &[], N),
+            Unit::new("ha", 1e4, &[("m", 2)], N),
+            Unit::new("au", 149597870700.0, &[("m", 1)], N),
+            Unit::new("ly", 9460730472580800.0, &[("m", 1)], N),
+            Unit::new("atm", 101325.0, &[("kg", 1), ("m", -1), ("s", -2)], N),
+            Unit::new("cal", 4.184, &[("kg", 1), ("m", 2), ("s", -2)], M),
+            // Imperial and US customary units.
+            Unit::new("inch", 0.0254, &[("m", 1)], N),
+            Unit::new("ft", 0.3048, &[("m", 1)], N),
+            Unit::new("yd", 0.9144, &[("m", 1)], N),
+            Unit::new("mi", 1609.344, &[("m", 1)], N),
+            Unit::new("nmi", 1852.0, &[("m", 1)], N),
+            Unit::new("mph", 0.44704, &[("m", 1), ("s", -1)], N),
+            Unit::new("kn", 1852.0 / 3600.0, &[("m", 1), ("s", -1)], N),
+            Unit::new("gal", 3.785411784e-3, &[("m", 3)], N),
+            Unit::new("lb", 0.45359237, &[("kg", 1)], N),
+            Unit::new("oz", 0.028349523125, &[("kg", 1)], N),
+            Unit::new("lbf", 4.4482216152605, &[("kg", 1), ("m", 1), ("s", -2)], N),
+            Unit::new("psi", 6894.757293168, &[("kg", 1), ("m", -1), ("s", -2)], N),
+            Unit::new(
+                "hp",
+                745.6998715822702,
+                &[("kg", 1), ("m", 2), ("s", -3)],
+                N,
+            ),
+        ])
+    })
+}
+
+pub fn fetch_rates() -> &'static Mutex<Option<Rates>> {
+    RATES.get_or_init(|| Mutex::new(None))
+}
+
+pub fn fetch_settings() -> &'static Mutex<Settings> {
+    SETTINGS.get_or_init(|| {
+        Mutex::new(Settings {
+            base: 10,
+            width: None,
+            modulus: None,
+            ties: Ties::Away,
+        })
+    })
+}
+
+pub fn fetch_random() -> &'static Mutex<Rng> {
+    RANDOM.get_or_init(|| Mutex::new(Rng::unseeded()))
+}
+
+pub fn fetch_parameters() -> &'static Mutex<Vec<String>> {
+    PARAMETERS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+pub fn fetch_bindings() -> &'static Mutex<Vec<(String, Value)>> {
+    BINDINGS.get_or_init(|| Mutex::new(Vec::new()))
+}
@@ -1,15 +1,103 @@
-use crate::eval::types::Expr;
+use crate::eval::{
+    environment,
+    integer::Integer,
+    types::{Expr, Function, Value},
+    unit::{self, Quantity},
+};
 
-pub mod func;
 pub mod bin;
+pub mod calculus;
+pub mod func;
+pub mod function;
+pub mod list;
+pub mod matrix;
+pub mod polynomial;
+pub mod solve;
 pub mod unary;
 
-pub fn calculate(expr: Expr) -> f64 {
+pub fn calculate(expr: &Expr) -> Result<Value, String> {
     match expr {
-        Expr::Num(n) => n,
+        Expr::Num(n) => Ok(Value::from(Quantity::from(*n))),
+        Expr::Int(n) => Ok(Value::Int(Integer::fit(n.clone()))),
+        Expr::Date(d) => Ok(Value::Date(d.clone())),
+        Expr::Dice(d) => Ok(Value::Int(Integer::fit(d.roll()))),
+        Expr::Unit(id) => match unit::find(id) {
+            // An affine unit alone has no amount to shift, unlike 20 °C.
+            Some(unit) if unit.offset != 0.0 => Err(format!(
+                "Affine unit '{}': Expected a number before it, as in 20 {}",
+                id, id
+            )),
+            Some(unit) => Ok(Value::from(Quantity::new(unit.factor, unit.dim))),
+            None => Err(format!("Unknown unit '{}': Expected a defined unit", id)),
+        },
         Expr::Func(id, args) => func::process(id, args),
         Expr::Bin(left, op, right) => bin::process(left, op, right),
         Expr::Unary(op, side) => unary::process(op, side),
-        Expr::Var(_id, value) => calculate(*value),
+        Expr::Var(_id, value) => calculate(value),
+        Expr::List(items) => items
+            .iter()
+            .map(calculate)
+            .collect::<Result<_, _>>()
+            .map(Value::List),
+        Expr::Range(start, end, step) => list::range(
+            calculate(start)?,
+            calculate(end)?,
+            step.as_ref().map(|step| calculate(step)).transpose()?,
+        ),
+        Expr::Index(items, index) => list::index(calculate(items)?, calculate(index)?),
+        Expr::Lambda(params, body) => Ok(Value::Function(Function::Lambda(
+            params.clone(),
+            Box::new(capture(params, body)),
+        ))),
+        Expr::Param(id) => environment::fetch_bindings()
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|(param, _)| param == id)
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| {
+                format!(
+                    "Unbound parameter '{}': Expected it to be used inside its lambda",
+                    id
+                )
+            }),
+        Expr::Call(callee, args) => {
+            let callee = calculate(callee)?;
+            let mut values = Vec::new();
+            for arg in args {
+                values.push(calculate(arg)?);
+            }
+            function::apply(callee, values)
+        }
+        Expr::Value(value) => Ok(value.clone()),
     }
 }
+
+// Runs a calculation in which the given parameters stand for these values, so that a body can
+// be calculated again and again without being copied for each call.
+pub fn bound<T>(params: &[String], args: Vec<Value>, calculate: impl FnOnce() -> T) -> T {
+    let depth = {
+        let mut bindings = environment::fetch_bindings().lock().unwrap();
+        bindings.extend(params.iter().cloned().zip(args));
+        bindings.len() - params.len()
+    };
+    let result = calculate();
+    environment::fetch_bindings()
+        .lock()
+        .unwrap()
+        .truncate(depth);
+    result
+}
+
+// A lambda made inside another one keeps the values of the outer parameters it uses.
+fn capture(params: &[String], body: &Expr) -> Expr {
+    let bindings = environment::fetch_bindings().lock().unwrap();
+    bindings
+        .iter()
+        .rev()
+        .filter(|(param, _)| !params.contains(param))
+        .fold(body.clone(), |body, (param, value)| {
+            body.substitute(param, value)
+        })
+}
@@ -1,26 +1,494 @@
+use num_bigint::BigInt;
+use num_integer::Integer as _;
+use num_traits::{Signed, ToPrimitive, Zero};
+
 use crate::eval::{
-    executor,
-    types::{Expr, Operator},
+    date,
+    executor::{self, list, matrix},
+    integer::{self, Integer, Width, theory},
+    matrix::is_matrix,
+    types::{Expr, Operator, Value},
+    unit::{self, Prefix, Quantity, Unit},
 };
 
-pub fn process(left: Box<Expr>, op: Operator, right: Box<Expr>) -> f64 {
-    let l = executor::calculate(*left);
-    let r = executor::calculate(*right);
+pub fn process(left: &Expr, op: &Operator, right: &Expr) -> Result<Value, String> {
+    match (op, right) {
+        (Operator::Conversion, _) => return convert(left, right),
+        // A number written with a unit, like 1.55 km, is shown in that unit until it's
+        // calculated with.
+        (Operator::Multiplication, Expr::Unit(id)) => {
+            if let Some(unit) = unit::find(id) {
+                let l = executor::calculate(left)?;
+                let written = match &l {
+                    Value::Int(_) => true,
+                    Value::Quantity(q) => q.dim.is_none() && q.unit.is_none(),
+                    _ => false,
+                };
+                if written || unit.offset != 0.0 {
+                    let l = l.quantity()?.scalar()?;
+                    let dim = unit.dim.clone();
+                    return Ok(Value::from(
+                        Quantity::new(l * unit.factor + unit.offset, dim).convert(unit)?,
+                    ));
+                }
+                let r = Value::from(Quantity::new(unit.factor, unit.dim));
+                return values(l, op.clone(), r);
+            }
+        }
+        // A percentage added to or taken off a value is a share of that value, as on a
+        // calculator, so 200 + 5% is 210.
+        (Operator::Addition | Operator::Subtraction, Expr::Unary(Operator::Percent, _)) => {
+            let l = executor::calculate(left)?;
+            let r = values(
+                l.clone(),
+                Operator::Multiplication,
+                executor::calculate(right)?,
+            )?;
+            return values(l, op.clone(), r);
+        }
+        _ => {}
+    }
+
+    let l = executor::calculate(left)?;
+    let r = match op {
+        Operator::Exponent => integer::unreduced(|| executor::calculate(right))?,
+        _ => executor::calculate(right)?,
+    };
+
+    values(l, op.clone(), r)
+}
+
+// Lists combine item by item, with a single value applying to every item.
+pub fn values(l: Value, op: Operator, r: Value) -> Result<Value, String> {
+    match (l, r) {
+        (Value::List(l), Value::List(r))
+            if matches!(op, Operator::Multiplication) && (is_matrix(&l) || is_matrix(&r)) =>
+        {
+            matrix::multiply(l, r)
+        }
+        (Value::List(l), r) if matches!(op, Operator::Exponent) && is_matrix(&l) => {
+            matrix::power(l, r)
+        }
+        (Value::List(l), Value::List(r)) => list::zip(l, r, |l, r| values(l, op.clone(), r)),
+        (Value::List(l), r) => list::map(l, |l| values(l, op.clone(), r.clone())),
+        (l, Value::List(r)) => list::map(r, |r| values(l.clone(), op.clone(), r)),
+        (Value::Int(l), Value::Int(r)) => integers(l, op, r),
+        (l, r) if op.bitwise() => integers(l.integer()?, op, r.integer()?),
+        (l @ Value::Date(_), r) | (l, r @ Value::Date(_)) => dates(l, op, r),
+        (l, r) if let Some(modulus) = integer::modulus() => {
+            integers(residue(l, &modulus)?, op, residue(r, &modulus)?)
+        }
+        (l, r) => quantity(l.quantity()?, op, r.quantity()?).map(Value::from),
+    }
+}
+
+fn quantity(l: Quantity, op: Operator, r: Quantity) -> Result<Quantity, String> {
+    if !matches!(op, Operator::Equal) {
+        l.absolute()?;
+        r.absolute()?;
+    }
 
     match op {
-        Operator::Addition => l + r,
-        Operator::Subtraction => l - r,
-        Operator::Multiplication => l * r,
-        Operator::Division => l / r,
-        Operator::Exponent => l.powf(r),
-        Operator::Percent => l * r / 100.0,
+        Operator::Addition => l.try_add(r),
+        Operator::Subtraction => l.try_sub(r),
+        Operator::Multiplication => Ok(l * r),
+        Operator::Division => Ok(l / r),
+        Operator::FloorDivision | Operator::Modulo | Operator::Remainder if r.value == 0.0 => {
+            Err(format!("Division by zero: Cannot take '{}{}0'", l, op))
+        }
+        Operator::FloorDivision => {
+            let q = l / r;
+            Ok(Quantity::new(q.value.floor(), q.dim))
+        }
+        // Floored modulo takes the sign of the divisor and the remainder that of the dividend.
+        Operator::Modulo => {
+            l.compatible(&r, "take the modulo of")?;
+            Ok(Quantity::new(
+                l.value - r.value * (l.value / r.value).floor(),
+                l.dim,
+            ))
+        }
+        Operator::Remainder => {
+            l.compatible(&r, "take the remainder of")?;
+            Ok(Quantity::new(l.value % r.value, l.dim))
+        }
+        Operator::Exponent => l.try_pow(r),
+        Operator::Percent => Ok(l * r / Quantity::from(100.0)),
+        Operator::Change => {
+            let change = r.try_sub(l.clone())?;
+            if l.value == 0.0 {
+                return Err(format!(
+                    "Division by zero: Cannot take a percent change from '{}'",
+                    l
+                ));
+            }
+            percent(change / l)
+        }
+        Operator::Proportion => {
+            l.compatible(&r, "compare")?;
+            if r.value == 0.0 {
+                return Err(format!(
+                    "Division by zero: Cannot take '{}' as a percentage of '{}'",
+                    l, r
+                ));
+            }
+            percent(l / r)
+        }
         Operator::Equal => {
-            if l == r {
-                1.0
+            l.compatible(&r, "compare")?;
+            if l.value == r.value {
+                Ok(Quantity::from(1.0))
             } else {
-                0.0
+                Ok(Quantity::from(0.0))
             }
         }
         _ => unreachable!(),
     }
 }
+
+// Modular arithmetic only works on whole numbers, so results like 4.5 can't be reduced.
+fn residue(value: Value, modulus: &BigInt) -> Result<Integer, String> {
+    let shown = value.to_string();
+    value.integer().map_err(|_| {
+        format!(
+            "Unexpected '{}': Expected a whole number while working modulo {}",
+            shown, modulus
+        )
+    })
+}
+
+// Shows a ratio as a percentage.
+fn percent(ratio: Quantity) -> Result<Quantity, String> {
+    ratio.convert(Unit::new("%", 0.01, &[], Prefix::None))
+}
+
+// Integers stay exact where they can and fall back to floating point for results such as 1/3.
+fn integers(l: Integer, op: Operator, r: Integer) -> Result<Value, String> {
+    let width = integer::width();
+    let (a, b) = (&l.value, &r.value);
+    let modulus = integer::modulus();
+    let exact = match op {
+        // In modular arithmetic, division multiplies by the inverse and powers stay reduced.
+        Operator::Division if let Some(modulus) = &modulus => {
+            let inverse = theory::inverse(b, modulus).ok_or(format!(
+                "Invalid division: '{}' has no inverse modulo {}",
+                r, modulus
+            ))?;
+            a * inverse
+        }
+        Operator::Exponent if let Some(modulus) = &modulus => theory::modpow(a, b, modulus)?,
+        Operator::Addition => a + b,
+        Operator::Subtraction => a - b,
+        Operator::Multiplication => a * b,
+        Operator::Division if width.is_some() && b.is_zero() => {
+            return Err(format!(
+                "Division by zero: Cannot divide '{}' by 0 in fixed-width integers",
+                l
+            ));
+        }
+        // With a fixed width, division truncates like it does in most languages.
+        Operator::Division if width.is_some() || (!b.is_zero() && (a % b).is_zero()) => a / b,
+        Operator::FloorDivision | Operator::Modulo | Operator::Remainder if b.is_zero() => {
+            return Err(format!("Division by zero: Cannot take '{}{}0'", l, op));
+        }
+        Operator::FloorDivision => a.div_floor(b),
+        Operator::Modulo => a.mod_floor(b),
+        Operator::Remainder => a % b,
+        Operator::Exponent if let Some(n) = power(a, b, width) => n,
+        Operator::BitAnd => a & b,
+        Operator::BitOr => a | b,
+        Operator::BitXor => a ^ b,
+        Operator::ShiftLeft | Operator::ShiftRight => shift(a, &op, b, width)?,
+        Operator::Equal if let Some(modulus) = &modulus => {
+            let equal = a.mod_floor(modulus) == b.mod_floor(modulus);
+            return Ok(Value::from(Quantity::from(f64::from(u8::from(equal)))));
+        }
+        Operator::Equal => return Ok(Value::from(Quantity::from(f64::from(u8::from(a == b))))),
+        _ if let Some(modulus) = &modulus => {
+            return Err(format!(
+                "Unsupported operation: '{}' has no meaning modulo {}",
+                op.to_string().trim(),
+                modulus
+            ));
+        }
+        _ => {
+            return quantity(Value::Int(l).quantity()?, op, Value::Int(r).quantity()?)
+                .map(Value::from);
+        }
+    };
+
+    // Only the operations of modular arithmetic are reduced, while others like mod work on the
+    // numbers themselves.
+    let result = match op {
+        Operator::Addition
+        | Operator::Subtraction
+        | Operator::Multiplication
+        | Operator::Division
+        | Operator::Exponent => Integer::reduce(exact),
+        _ => Integer::fit(exact),
+    }
+    .flags(&l, &r);
+    let Some(width) = width else {
+        return Ok(Value::Int(result));
+    };
+
+    // The same bits read as signed or unsigned can wrap in one reading and not the other, so
+    // these operations check both.
+    let both = |x: BigInt, y: BigInt| match op {
+        Operator::Addition => Some(x + y),
+        Operator::Subtraction => Some(x - y),
+        Operator::Multiplication => Some(x * y),
+        Operator::ShiftLeft => shift(&x, &op, b, Some(width)).ok(),
+        _ => None,
+    };
+    let unsigned = Width {
+        signed: false,
+        ..width
+    };
+    let signed = Width {
+        signed: true,
+        ..width
+    };
+    let mut result = result;
+    if let Some(n) = both(unsigned.wrap(a), unsigned.wrap(b)) {
+        result.carry |= !unsigned.contains(&n);
+    }
+    if let Some(n) = both(signed.wrap(a), signed.wrap(b)) {
+        result.overflow |= !signed.contains(&n);
+    }
+
+    Ok(Value::Int(result))
+}
+
+// Powers are exact unless the result would be too large to be useful. In a fixed width only the
+// low bits matter, so they're found with modular exponentiation and offset by the modulus to keep
+// the result flagged.
+fn power(a: &BigInt, b: &BigInt, width: Option<Width>) -> Option<BigInt> {
+    // Integer division makes negative powers zero, apart from those of 1 and -1.
+    if b.is_negative() {
+        width?;
+        return Some(
+            match a.magnitude() == &1u32.into() && (b % 2u32).is_zero() {
+                true => BigInt::from(1),
+                false if a.magnitude() == &1u32.into() => a.clone(),
+                false => BigInt::zero(),
+            },
+        );
+    }
+
+    let n = b.to_u32()?;
+    if a.magnitude() <= &1u32.into() {
+        return Some(a.pow(n));
+    }
+
+    match width {
+        Some(width) if n > width.bits => {
+            let modulus = width.modulus();
+            Some(width.unsigned(a).modpow(b, &modulus) + modulus)
+        }
+        None if a.bits() * n as u64 > 1 << 20 => None,
+        _ => Some(a.pow(n)),
+    }
+}
+
+// Negative counts shift the other way. In a fixed width, counts past the width are capped since
+// every bit has been shifted out by then.
+fn shift(a: &BigInt, op: &Operator, b: &BigInt, width: Option<Width>) -> Result<BigInt, String> {
+    let count = b.to_i64().unwrap_or(match b.is_negative() {
+        true => -i64::MAX,
+        false => i64::MAX,
+    });
+    let mut left = match op {
+        Operator::ShiftLeft => count,
+        _ => -count,
+    };
+    if let Some(width) = width {
+        let bits = width.bits as i64 + 1;
+        left = left.clamp(-bits, bits);
+    }
+
+    if left > 1 << 20 {
+        return Err(format!(
+            "Invalid shift '{}': Expected a left shift of at most {} bits",
+            b,
+            1 << 20
+        ));
+    }
+
+    Ok(match left >= 0 {
+        true => a << left as usize,
+        false => a >> left.unsigned_abs().min(a.bits() + 1) as usize,
+    })
+}
+
+fn dates(l: Value, op: Operator, r: Value) -> Result<Value, String> {
+    // Whole numbers next to a date are read as durations or counts like any other number.
+    let number = |value: Value| match value {
+        Value::Int(_) => value.quantity().map(Value::from),
+        value => Ok(value),
+    };
+
+    match (number(l)?, op, number(r)?) {
+        (Value::Date(d), Operator::Addition, Value::Quantity(q))
+        | (Value::Quantity(q), Operator::Addition, Value::Date(d)) => Ok(Value::Date(d.add(q)?)),
+        (Value::Date(d), Operator::Subtraction, Value::Quantity(q)) => Ok(Value::Date(d.add(-q)?)),
+        (Value::Date(a), Operator::Subtraction, Value::Date(b)) => {
+            let duration = date::seconds(a.secs - b.secs);
+            if duration.value.abs() < 86400.0 {
+                return Ok(Value::from(duration));
+            }
+
+            // Spans of a day or more read better in days than in seconds.
+            match unit::find("d") {
+                Some(day) => Ok(Value::from(duration.convert(day)?)),
+                None => Ok(Value::from(duration)),
+            }
+        }
+        (Value::Date(a), Operator::Equal, Value::Date(b)) => {
+            Ok(Value::from(Quantity::from(if a.secs == b.secs {
+                1.0
+            } else {
+                0.0
+            })))
+        }
+        (l, op, r) => Err(format!(
+            "Invalid operation: Cannot apply '{}' to '{}' and '{}'",
+            op.to_string().trim(),
+            l,
+            r
+        )),
+    }
+}
+
+fn convert(left: &Expr, right: &Expr) -> Result<Value, String> {
+    to(executor::calculate(left)?, right)
+}
+
+fn to(value: Value, right: &Expr) -> Result<Value, String> {
+    let quantity = match value {
+        Value::List(items) => return list::map(items, |item| to(item, right)),
+        // Converting a date to an offset such as +02:00 or UTC shows it in that offset.
+        Value::Date(date) => {
+            let offset = executor::calculate(right)?.quantity()?;
+            return Ok(Value::Date(date.with_offset(offset)?));
+        }
+        value => value.quantity()?,
+    };
+
+    // A lone unit keeps its offset so that affine units like °C convert correctly.
+    if let Expr::Unit(id) = right
+        && let Some(unit) = unit::find(id)
+    {
+        return Ok(Value::from(quantity.convert(unit)?));
+    }
+
+    let name = right.to_string();
+    let target = executor::calculate(right)?.quantity()?;
+    Ok(Value::from(quantity.convert(Unit {
+        name,
+        factor: target.value,
+        offset: 0.0,
+        dim: target.dim,
+        prefix: Prefix::None,
+    })?))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval::session::{eval, modulo};
+
+    #[test]
+    fn percent_of_left_side() {
+        assert_eq!(eval("200 + 5%"), "210");
+        assert_eq!(eval("200 - 5%"), "190");
+        assert_eq!(eval("200 + 5% + 5%"), "220.5");
+        assert_eq!(eval("200 + (2 + 3)%"), "210");
+        assert_eq!(eval("200 + -5%"), "190");
+        assert_eq!(eval("2 m + 50%"), "3 m");
+    }
+
+    #[test]
+    fn percent_as_factor() {
+        assert_eq!(eval("200 * 5%"), "10");
+        assert_eq!(eval("200 / 5%"), "4000");
+        assert_eq!(eval("5% of 200"), "10");
+        assert_eq!(eval("10% of 10% of 200"), "2");
+    }
+
+    #[test]
+    fn percent_alone() {
+        assert_eq!(eval("5%"), "0.05");
+        assert_eq!(eval("-5%"), "-0.05");
+        assert_eq!(eval("(2 + 3)%"), "0.05");
+        assert_eq!(eval("sin(pi / 2)%"), "0.01");
+    }
+
+    #[test]
+    fn percent_change() {
+        assert_eq!(eval("100 -> 125"), "25 %");
+        assert_eq!(eval("125 -> 100"), "-20 %");
+        assert_eq!(eval("0.5 -> 0.75"), "50 %");
+        assert_eq!(eval("1 m -> 150 cm"), "50 %");
+        eval("before = 100");
+        eval("after = 125");
+        assert_eq!(eval("(before) -> after"), "25 %");
+        assert_eq!(
+            eval("0 -> 5"),
+            "Division by zero: Cannot take a percent change from '0'"
+        );
+    }
+
+    #[test]
+    fn name_before_arrow_is_parameter() {
+        eval("h = t -> t * 3");
+        assert_eq!(eval("h(2)"), "6");
+        eval("sq = s -> s^2");
+        assert_eq!(eval("sq(3)"), "9");
+        assert_eq!(eval("(s -> s^2)(3)"), "9");
+        eval("count = 4");
+        assert_eq!(eval("(count -> count + 1)(1)"), "2");
+    }
+
+    #[test]
+    fn percent_proportion() {
+        assert_eq!(eval("30 as % of 120"), "25 %");
+        assert_eq!(eval("50 cm as % of 2 m"), "25 %");
+        assert_eq!(
+            eval("5 as % of 0"),
+            "Division by zero: Cannot take '5' as a percentage of '0'"
+        );
+        assert_eq!(
+            eval("1 m as % of 1 s"),
+            "Incompatible units: Cannot compare 'm' and 's'"
+        );
+    }
+
+    #[test]
+    fn modulus_reduces_modular_operations() {
+        assert_eq!(
+            modulo(
+                7,
+                &["3 / 5", "2^100", "-3", "5 * 4 + 1", "10 = 3", "[10, 11]"]
+            ),
+            ["2", "2", "4", "0", "1", "[3, 4]"]
+        );
+    }
+
+    #[test]
+    fn modulus_leaves_function_arguments_whole() {
+        assert_eq!(
+            modulo(
+                7,
+                &[
+                    "isprime(11)",
+                    "10!",
+                    "(3 + 7)!",
+                    "fib(8)",
+                    "8 mod 3",
+                    "modpow(2, 10, 1000)"
+                ]
+            ),
+            ["1", "0", "0", "0", "2", "3"]
+        );
+    }
+}
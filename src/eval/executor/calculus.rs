@@ -0,0 +1,32 @@
+use crate::eval::{calculus, executor::function, types::Value, unit::Quantity};
+
+// Derivatives and integrals of a function of one value, which an expression with its variable
+// named arrives as too. Its body is calculated afresh for each point, with the variable bound
+// to the point.
+pub fn process(id: &str, mut values: Vec<Value>) -> Result<Value, String> {
+    let function = match values.remove(0) {
+        Value::Function(function) => function,
+        value => {
+            return Err(format!(
+                "Unexpected '{}': Expected a function or an expression with its variable for '{}'",
+                value, id
+            ));
+        }
+    };
+    let f = |x: f64| {
+        function::call(&function, vec![Value::from(Quantity::from(x))])?
+            .quantity()?
+            .scalar()
+    };
+
+    let mut points = Vec::new();
+    for value in values {
+        points.push(value.quantity()?.scalar()?);
+    }
+    let value = match id {
+        "deriv" => calculus::derivative(f, points[0])?,
+        "integrate" => calculus::integral(f, points[0], points[1])?,
+        _ => unreachable!(),
+    };
+    Ok(Value::from(Quantity::from(value)))
+}
@@ -1,17 +1,490 @@
-use crate::eval::{executor, types::Expr};
+use std::cmp::Ordering;
 
-pub fn process(id: String, args: Vec<Box<Expr>>) -> f64 {
-    let mut nums = Vec::new();
-    for arg in args.iter() {
-        nums.push(executor::calculate((**arg).clone()))
+use num_bigint::BigInt;
+use num_integer::Integer as _;
+use num_traits::{One, Signed, Zero};
+
+use crate::eval::{
+    date::{self, Date},
+    distribution,
+    executor::{self, calculus, function, list, matrix, polynomial, solve},
+    float,
+    integer::{self, Integer, theory},
+    random, rounding, special, stats,
+    types::{Expr, Function, Value},
+    unit::Quantity,
+};
+
+pub fn process(id: &str, args: &[Expr]) -> Result<Value, String> {
+    // Arguments aren't reduced by the session modulus, so that isprime(11) still sees 11.
+    let mut values = Vec::new();
+    for arg in args {
+        values.push(integer::unreduced(|| executor::calculate(arg))?);
+    }
+
+    call(id.to_string(), values)
+}
+
+pub fn call(id: String, mut values: Vec<Value>) -> Result<Value, String> {
+    let aggregate = matches!(
+        id.as_str(),
+        "mean"
+            | "median"
+            | "mode"
+            | "var"
+            | "varp"
+            | "stdev"
+            | "stdevp"
+            | "quantile"
+            | "min"
+            | "max"
+            | "sum"
+            | "prod"
+            | "range"
+            | "geomean"
+            | "harmean"
+            | "gcd"
+            | "lcm"
+    );
+    let whole = matches!(
+        id.as_str(),
+        "len"
+            | "map"
+            | "filter"
+            | "reduce"
+            | "sort"
+            | "zip"
+            | "compose"
+            | "transpose"
+            | "det"
+            | "inv"
+            | "rank"
+            | "trace"
+            | "dot"
+            | "cross"
+            | "norm"
+            | "linsolve"
+            | "solve"
+            | "lu"
+            | "qr"
+            | "cholesky"
+            | "svd"
+            | "eig"
+            | "roots"
+            | "polyval"
+            | "polymul"
+            | "polydiv"
+            | "polyder"
+            | "deriv"
+            | "integrate"
+    );
+
+    // Functions of one value apply to each item of a list in their first argument, while
+    // functions of many values take the items of lists as their values.
+    match values.first() {
+        Some(Value::List(_)) if !aggregate && !whole => {
+            let Value::List(items) = values.remove(0) else {
+                unreachable!()
+            };
+            return list::map(items, |item| {
+                let mut values = values.clone();
+                values.insert(0, item);
+                call(id.clone(), values)
+            });
+        }
+        // log(x) and log_2(x) take their base first, so they apply to each item of a list after
+        // it.
+        Some(_) if id == "log" && matches!(values.get(1), Some(Value::List(_))) => {
+            let Value::List(items) = values.remove(1) else {
+                unreachable!()
+            };
+            return list::map(items, |item| {
+                let mut values = values.clone();
+                values.push(item);
+                call(id.clone(), values)
+            });
+        }
+        // gcd and lcm of a single number would just be that number, so they take one argument
+        // only when it's a list.
+        Some(value)
+            if values.len() == 1
+                && !matches!(value, Value::List(_))
+                && matches!(id.as_str(), "gcd" | "lcm") =>
+        {
+            return Err(format!(
+                "Wrong number of arguments: '{}' takes at least 2 numbers or a list but got 1",
+                id
+            ));
+        }
+        _ if aggregate => {
+            values = list::flatten(values);
+            // An empty sum is 0 and an empty product is 1.
+            match id.as_str() {
+                "sum" if values.is_empty() => return Ok(Value::Int(Integer::fit(BigInt::zero()))),
+                "prod" if values.is_empty() => return Ok(Value::Int(Integer::fit(BigInt::one()))),
+                _ => {}
+            }
+            if values.is_empty() {
+                return Err(format!(
+                    "Not enough values: '{}' needs at least one value",
+                    id
+                ));
+            }
+        }
+        _ => {}
     }
 
     match id.as_str() {
-        "sqrt" => nums[0].sqrt(),
+        "len" => Ok(Value::Int(Integer::fit(BigInt::from(
+            items(&id, values.remove(0))?.len(),
+        )))),
+        "map" | "filter" | "reduce" | "sort" | "zip" | "compose" => functions(&id, values),
+        "identity" | "transpose" | "det" | "inv" | "rank" | "trace" | "dot" | "cross" | "norm"
+        | "linsolve" | "lu" | "qr" | "cholesky" | "svd" | "eig" => matrix::process(&id, values),
+        "solve" => solve::process(values),
+        "deriv" | "integrate" => calculus::process(&id, values),
+        "roots" | "polyval" | "polymul" | "polydiv" | "polyder" => polynomial::process(&id, values),
+        "now" | "weekday" | "isoweek" | "unix" | "fromunix" => dates(&id, values),
+        "rand" => Ok(Value::from(Quantity::from(random::float()))),
+        "randn" => Ok(Value::from(Quantity::from(random::normal()))),
+        "randint" => {
+            let high = values.pop().unwrap().integer()?;
+            let low = values.pop().unwrap().integer()?;
+            Ok(Value::Int(Integer::fit(random::integer(
+                &low.value,
+                &high.value,
+            )?)))
+        }
+        "bits" => {
+            let mut args = Vec::new();
+            for value in values {
+                args.push(value.quantity()?.scalar()?);
+            }
+
+            let width = args.get(1).copied().unwrap_or(64.0);
+            if width.fract() != 0.0 {
+                return Err(format!(
+                    "Invalid float width '{}': Expected 16, 32 or 64",
+                    Quantity::from(width)
+                ));
+            }
+            Ok(Value::Text(float::inspect(args[0], width as u32)?))
+        }
+        // Whole numbers are already rounded, and stay exact.
+        "floor" | "ceil" | "trunc" | "round" | "sign"
+            if values.len() == 1 && matches!(values[0], Value::Int(_)) =>
+        {
+            let Some(Value::Int(i)) = values.pop() else {
+                unreachable!()
+            };
+            match id.as_str() {
+                "sign" => Ok(Value::Int(Integer::fit(i.value.signum()))),
+                _ => Ok(Value::Int(i)),
+            }
+        }
+        "mean" | "median" | "mode" | "var" | "varp" | "stdev" | "stdevp" | "quantile" | "min"
+        | "max" | "sum" | "prod" | "range" | "geomean" | "harmean" => statistics(&id, values),
+        "nCr" | "nPr" | "gcd" | "lcm" | "isprime" | "nextprime" | "factor" | "totient"
+        | "divisors" | "fib" | "modpow" => {
+            let mut ints = Vec::new();
+            for value in values {
+                ints.push(value.integer()?.value);
+            }
+
+            integers(&id, ints)
+        }
+        _ => {
+            let mut quantities = Vec::new();
+            for value in values {
+                quantities.push(value.quantity()?);
+            }
+
+            numbers(&id, quantities).map(Value::from)
+        }
+    }
+}
+
+fn dates(id: &str, mut values: Vec<Value>) -> Result<Value, String> {
+    if id == "now" {
+        return Ok(Value::Date(Date::now()));
+    }
+
+    let value = values.remove(0);
+    let n = match id {
+        "weekday" => value.date()?.weekday() as f64,
+        "isoweek" => value.date()?.isoweek() as f64,
+        "unix" => value.date()?.secs,
+        "fromunix" => {
+            let secs = value.quantity()?;
+            let secs = match secs.dim.is_none() {
+                true => secs.value,
+                false => secs.try_sub(date::seconds(0.0))?.value,
+            };
+            return Ok(Value::Date(Date::new(secs, 0)));
+        }
+        _ => unreachable!(),
+    };
+
+    Ok(Value::from(Quantity::from(n)))
+}
+
+fn statistics(id: &str, mut values: Vec<Value>) -> Result<Value, String> {
+    // Sums, products and extremes of whole numbers stay exact.
+    if matches!(id, "sum" | "prod" | "min" | "max")
+        && values.iter().all(|value| matches!(value, Value::Int(_)))
+    {
+        let ints = values.into_iter().map(|value| match value {
+            Value::Int(i) => i.value,
+            _ => unreachable!(),
+        });
+        let n = match id {
+            "sum" => ints.sum(),
+            "prod" => ints.product(),
+            "min" => ints.min().unwrap(),
+            _ => ints.max().unwrap(),
+        };
+        return Ok(Value::Int(Integer::fit(n)));
+    }
+
+    let p = match id {
+        "quantile" if values.len() < 2 => {
+            return Err(
+                "Wrong number of arguments: 'quantile' takes values followed by a fraction".into(),
+            );
+        }
+        "quantile" => values.pop().unwrap().quantity()?.scalar()?,
+        _ => 0.5,
+    };
+
+    let mut quantities = Vec::new();
+    for value in values {
+        quantities.push(value.quantity()?);
+    }
+
+    if id == "prod" {
+        return Ok(Value::from(
+            quantities
+                .into_iter()
+                .fold(Quantity::from(1.0), |product, q| product * q),
+        ));
+    }
+
+    // Everything else needs values in the same units, and gives a result in them.
+    let dim = quantities[0].dim.clone();
+    let mut xs = Vec::new();
+    for q in quantities.iter() {
+        quantities[0].compatible(q, "combine")?;
+        xs.push(q.value);
+    }
+    // An undefined value like 0/0 has no place in an order or a count.
+    if xs.iter().any(|x| x.is_nan()) {
+        return Err(format!(
+            "Invalid value 'NaN' for '{}': Expected numbers that are defined",
+            id
+        ));
+    }
+
+    let n = match id {
+        "mean" => stats::mean(&xs),
+        "median" => stats::quantile(&xs, 0.5)?,
+        "quantile" => stats::quantile(&xs, p)?,
+        "mode" => stats::mode(&xs),
+        "var" => {
+            return Ok(Value::from(Quantity::new(
+                stats::variance(&xs, 1)?,
+                dim.powi(2),
+            )));
+        }
+        "varp" => {
+            return Ok(Value::from(Quantity::new(
+                stats::variance(&xs, 0)?,
+                dim.powi(2),
+            )));
+        }
+        "stdev" => stats::variance(&xs, 1)?.sqrt(),
+        "stdevp" => stats::variance(&xs, 0)?.sqrt(),
+        "min" => xs.iter().copied().fold(f64::INFINITY, f64::min),
+        "max" => xs.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        "sum" => stats::sum(&xs),
+        "range" => {
+            let min = xs.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = xs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            max - min
+        }
+        "geomean" => stats::geomean(&xs)?,
+        "harmean" => stats::harmean(&xs)?,
+        _ => unreachable!(),
+    };
+
+    Ok(Value::from(Quantity::new(n, dim)))
+}
+
+// Exact functions of whole numbers.
+fn integers(id: &str, ints: Vec<BigInt>) -> Result<Value, String> {
+    let n = match id {
+        "nCr" => theory::choose(&ints[0], &ints[1])?,
+        "nPr" => theory::permutations(&ints[0], &ints[1])?,
+        "gcd" => ints.iter().fold(BigInt::zero(), |a, b| a.gcd(b)),
+        "lcm" => ints.iter().fold(BigInt::one(), |a, b| a.lcm(b)),
+        "isprime" => BigInt::from(u8::from(theory::is_prime(&ints[0]))),
+        "nextprime" => theory::next_prime(&ints[0]),
+        "totient" => theory::totient(&ints[0])?,
+        "fib" => theory::fib(&ints[0])?,
+        "modpow" => theory::modpow(&ints[0], &ints[1], &ints[2])?,
+        "factor" => {
+            let mut factors: Vec<String> = theory::factor(id, &ints[0])?
+                .into_iter()
+                .map(|(p, power)| match power {
+                    1 => p.to_string(),
+                    _ => format!("{}^{}", p, power),
+                })
+                .collect();
+            if ints[0].is_negative() {
+                factors.insert(0, "-1".to_string());
+            }
+            if factors.is_empty() {
+                factors.push("1".to_string());
+            }
+            return Ok(Value::Text(factors.join(" * ")));
+        }
+        "divisors" => {
+            let divisors: Vec<String> = theory::divisors(&ints[0])?
+                .iter()
+                .map(|d| d.to_string())
+                .collect();
+            return Ok(Value::Text(divisors.join(", ")));
+        }
+        _ => unreachable!(),
+    };
+
+    Ok(Value::Int(Integer::fit(n)))
+}
+
+// Applies a function to a quantity's value in the unit it's shown in, keeping that unit.
+fn shown(q: Quantity, f: impl Fn(f64) -> f64) -> Quantity {
+    let (factor, offset) = q
+        .unit
+        .as_ref()
+        .map_or((1.0, 0.0), |unit| (unit.factor, unit.offset));
+    let value = f((q.value - offset) / factor) * factor + offset;
+    Quantity {
+        value,
+        radix: None,
+        ..q
+    }
+}
+
+fn numbers(id: &str, mut quantities: Vec<Quantity>) -> Result<Quantity, String> {
+    match id {
+        "sqrt" => return quantities.remove(0).try_pow(Quantity::from(0.5)),
+        "cbrt" => return quantities.remove(0).try_pow(Quantity::from(1.0 / 3.0)),
+        "root" => {
+            let n = quantities[1].scalar()?;
+            return quantities.remove(0).try_pow(Quantity::from(1.0 / n));
+        }
+        // Rounding works on the value as it's shown, so 1.55 km rounds to 2 km rather than to
+        // a whole number of metres.
+        "floor" | "ceil" | "trunc" | "frac" | "round" => {
+            let q = quantities.remove(0);
+            let places = match quantities.first() {
+                Some(places) => {
+                    let n = places.scalar()?;
+                    if n.fract() != 0.0 {
+                        return Err(format!(
+                            "Invalid places '{}': Expected a whole number of decimal places",
+                            places
+                        ));
+                    }
+                    Some(n.clamp(i32::MIN as f64, i32::MAX as f64) as i32)
+                }
+                None => None,
+            };
+            return Ok(shown(q, |x| match id {
+                "floor" => x.floor(),
+                "ceil" => x.ceil(),
+                "trunc" => x.trunc(),
+                "frac" => x.fract(),
+                _ => match places {
+                    Some(places) => rounding::places(x, places),
+                    None => rounding::whole(x),
+                },
+            }));
+        }
+        "roundto" => {
+            let step = quantities.remove(1);
+            let q = quantities.remove(0);
+            q.compatible(&step, "round")?;
+            if step.value == 0.0 {
+                return Err(format!(
+                    "Division by zero: Cannot round '{}' to steps of '{}'",
+                    q, step
+                ));
+            }
+            let factor = q.unit.as_ref().map_or(1.0, |unit| unit.factor);
+            let step = step.value / factor;
+            return Ok(shown(q, |x| rounding::whole(x / step) * step));
+        }
+        "clamp" => {
+            let high = quantities.remove(2);
+            let low = quantities.remove(1);
+            let q = quantities.remove(0);
+            q.compatible(&low, "clamp")?;
+            q.compatible(&high, "clamp")?;
+            if low.value > high.value {
+                return Err(format!(
+                    "Invalid range for 'clamp': Expected '{}' to be at most '{}'",
+                    low, high
+                ));
+            }
+            return Ok(Quantity::new(q.value.clamp(low.value, high.value), q.dim));
+        }
+        "sign" => {
+            let value = quantities[0].value;
+            return Ok(Quantity::from(if value == 0.0 {
+                0.0
+            } else {
+                value.signum()
+            }));
+        }
+        _ => {}
+    }
+
+    let mut nums = Vec::new();
+    for quantity in quantities.iter() {
+        nums.push(quantity.scalar()?);
+    }
+
+    let n = match id {
+        "gamma" => special::gamma(nums[0])?,
+        "lgamma" => special::lgamma(nums[0])?,
+        "beta" => special::beta(nums[0], nums[1])?,
+        "digamma" => special::digamma(nums[0])?,
+        "erf" => special::erf(nums[0]),
+        "erfc" => special::erfc(nums[0]),
+
+        // The normal distribution defaults to the standard one.
+        "normpdf" | "normcdf" | "norminv" => {
+            let (mu, sigma) = match nums.len() {
+                1 => (0.0, 1.0),
+                _ => (nums[1], nums[2]),
+            };
+            match id {
+                "normpdf" => distribution::normpdf(nums[0], mu, sigma)?,
+                "normcdf" => distribution::normcdf(nums[0], mu, sigma)?,
+                _ => distribution::norminv(nums[0], mu, sigma)?,
+            }
+        }
+        "binompdf" => distribution::binompdf(nums[0], nums[1], nums[2])?,
+        "binomcdf" => distribution::binomcdf(nums[0], nums[1], nums[2])?,
+        "poissonpdf" => distribution::poissonpdf(nums[0], nums[1])?,
+        "poissoncdf" => distribution::poissoncdf(nums[0], nums[1])?,
+        "tcdf" => distribution::tcdf(nums[0], nums[1])?,
+        "chi2cdf" => distribution::chi2cdf(nums[0], nums[1])?,
+        "expcdf" => distribution::expcdf(nums[0], nums[1])?,
+        "unifcdf" => distribution::unifcdf(nums[0], nums[1], nums[2])?,
+
         "ln" => nums[0].ln(),
-        "root" => nums[0].powf(1.0 / nums[1]),
         "log" => nums[1].log(nums[0]),
-        "cbrt" => nums[0].powf(1.0 / 3.0),
 
         "sin" => nums[0].sin(),
         "cos" => nums[0].cos(),
@@ -41,5 +514,141 @@ pub fn process(id: String, args: Vec<Box<Expr>>) -> f64 {
         "asech" => 1.0 / nums[0].acosh(),
         "acsch" => 1.0 / nums[0].asinh(),
         _ => unreachable!(),
+    };
+
+    Ok(Quantity::from(n))
+}
+
+fn items(id: &str, value: Value) -> Result<Vec<Value>, String> {
+    match value {
+        Value::List(items) => Ok(items),
+        value => Err(format!(
+            "Unexpected '{}': Expected a list for '{}'",
+            value, id
+        )),
+    }
+}
+
+fn function(id: &str, value: Value) -> Result<Function, String> {
+    match value {
+        Value::Function(function) => Ok(function),
+        value => Err(format!(
+            "Unexpected '{}': Expected a function for '{}'",
+            value, id
+        )),
+    }
+}
+
+// Functions that take other functions, or work on whole lists rather than on each item.
+fn functions(id: &str, mut values: Vec<Value>) -> Result<Value, String> {
+    match id {
+        "map" => {
+            let xs = items(id, values.remove(1))?;
+            let f = function(id, values.remove(0))?;
+            list::map(xs, |x| function::call(&f, vec![x]))
+        }
+        "filter" => {
+            let xs = items(id, values.remove(1))?;
+            let p = function(id, values.remove(0))?;
+            let mut kept = Vec::new();
+            for x in xs {
+                if truthy(function::call(&p, vec![x.clone()])?)? {
+                    kept.push(x);
+                }
+            }
+            Ok(Value::List(kept))
+        }
+        "reduce" => {
+            let init = values.get(2).cloned();
+            let mut xs = items(id, values.remove(1))?.into_iter();
+            let f = function(id, values.remove(0))?;
+            let Some(mut result) = init.or_else(|| xs.next()) else {
+                return Err(
+                    "Not enough values: 'reduce' needs a starting value for an empty list".into(),
+                );
+            };
+            for x in xs {
+                result = function::call(&f, vec![result, x])?;
+            }
+            Ok(result)
+        }
+        // Sorts in increasing order, or by what a function gives for each item.
+        "sort" => {
+            let key = values
+                .get(1)
+                .cloned()
+                .map(|f| function(id, f))
+                .transpose()?;
+            let xs = items(id, values.remove(0))?;
+            let keys = match &key {
+                Some(key) => xs
+                    .iter()
+                    .map(|x| function::call(key, vec![x.clone()]))
+                    .collect::<Result<Vec<_>, _>>()?,
+                None => xs.clone(),
+            };
+
+            let mut error = None;
+            let mut order: Vec<usize> = (0..xs.len()).collect();
+            order.sort_by(|&i, &j| {
+                compare(&keys[i], &keys[j]).unwrap_or_else(|e| {
+                    error.get_or_insert(e);
+                    Ordering::Equal
+                })
+            });
+            if let Some(error) = error {
+                return Err(error);
+            }
+            Ok(Value::List(
+                order.into_iter().map(|i| xs[i].clone()).collect(),
+            ))
+        }
+        "zip" => {
+            let ys = items(id, values.remove(1))?;
+            let xs = items(id, values.remove(0))?;
+            list::zip(xs, ys, |x, y| Ok(Value::List(vec![x, y])))
+        }
+        "compose" => {
+            let mut functions = Vec::new();
+            for value in values {
+                functions.push(function(id, value)?);
+            }
+            Ok(Value::Function(Function::Compose(functions)))
+        }
+        _ => unreachable!(),
+    }
+}
+
+// Zero is false and any other number is true, as given by '='.
+fn truthy(value: Value) -> Result<bool, String> {
+    match value {
+        Value::Int(i) => Ok(!i.value.is_zero()),
+        value => Ok(value.quantity()?.value != 0.0),
+    }
+}
+
+fn compare(a: &Value, b: &Value) -> Result<Ordering, String> {
+    match (a, b) {
+        (Value::Int(a), Value::Int(b)) => Ok(a.value.cmp(&b.value)),
+        (a, b) => {
+            let a = a.clone().quantity()?;
+            let b = b.clone().quantity()?;
+            a.compatible(&b, "compare")?;
+            Ok(a.value.total_cmp(&b.value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval::session::eval;
+
+    #[test]
+    fn functions_of_one_value_apply_to_each_item() {
+        assert_eq!(eval("sqrt([4, 9])"), "[2, 3]");
+        assert_eq!(eval("log([10, 100])"), "[1, 2]");
+        assert_eq!(eval("log_2([8, 16])"), "[3, 4]");
+        assert_eq!(eval("root([8, 27], 3)"), "[2, 3]");
+        assert_eq!(eval("round([1.25, 2.75], 1)"), "[1.3, 2.8]");
     }
 }
@@ -0,0 +1,51 @@
+use crate::eval::{
+    executor::{self, bin, func},
+    parser::prefix::ident::func::check,
+    types::{Function, Operator, Value},
+    unit::Quantity,
+};
+
+// Calls a function, or multiplies when a name followed by parentheses holds a number.
+pub fn apply(callee: Value, mut args: Vec<Value>) -> Result<Value, String> {
+    match callee {
+        Value::Function(function) => call(&function, args),
+        value if args.len() == 1 => bin::values(value, Operator::Multiplication, args.remove(0)),
+        value => Err(format!(
+            "Unexpected '{}': Only functions can be called with several arguments",
+            value
+        )),
+    }
+}
+
+pub fn call(function: &Function, mut args: Vec<Value>) -> Result<Value, String> {
+    match function {
+        Function::Lambda(params, body) => {
+            if params.len() != args.len() {
+                return Err(format!(
+                    "Wrong number of arguments: '{}' takes {} but got {}",
+                    function,
+                    params.len(),
+                    args.len()
+                ));
+            }
+
+            executor::bound(params, args, || executor::calculate(body))
+        }
+        Function::Builtin(id) => {
+            check(id, args.len())?;
+            if id == "log" && args.len() == 1 {
+                args.insert(0, Value::from(Quantity::from(10.0)));
+            }
+            func::call(id.to_string(), args)
+        }
+        // The last function is applied first, as in f(g(x)).
+        Function::Compose(functions) => {
+            let mut functions = functions.iter().rev();
+            let mut value = call(functions.next().unwrap(), args)?;
+            for function in functions {
+                value = call(function, vec![value])?;
+            }
+            Ok(value)
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use num_bigint::BigInt;
+use num_integer::Integer as _;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use crate::eval::{integer::Integer, types::Value, unit::Quantity};
+
+// The longest list a range may make, so that a typo like 1..10^9 fails quickly.
+const LIMIT: usize = 1_000_000;
+
+pub fn range(start: Value, end: Value, step: Option<Value>) -> Result<Value, String> {
+    // Whole numbers stay exact.
+    if let (Value::Int(start), Value::Int(end)) = (&start, &end)
+        && matches!(step, None | Some(Value::Int(_)))
+    {
+        let step = match step {
+            Some(Value::Int(step)) => step.value,
+            _ if end.value < start.value => BigInt::from(-1),
+            _ => BigInt::from(1),
+        };
+        if step.is_zero() {
+            return Err(format!(
+                "Invalid step for '{}..{}': Expected a step other than 0",
+                start, end
+            ));
+        }
+
+        let count: BigInt = (&end.value - &start.value).div_floor(&step) + 1;
+        let count = length(count.to_f64().unwrap_or(f64::INFINITY))?;
+        let mut items = Vec::with_capacity(count);
+        let mut n = start.value.clone();
+        for _ in 0..count {
+            items.push(Value::Int(Integer::fit(n.clone())));
+            n += &step;
+        }
+        return Ok(Value::List(items));
+    }
+
+    let start = start.quantity()?.scalar()?;
+    let end = end.quantity()?.scalar()?;
+    let step = match step {
+        Some(step) => step.quantity()?.scalar()?,
+        None if end < start => -1.0,
+        None => 1.0,
+    };
+    if step == 0.0 {
+        return Err(format!(
+            "Invalid step for '{}..{}': Expected a step other than 0",
+            start, end
+        ));
+    }
+
+    // Each value is worked out from the start rather than added up, and a little slack lets
+    // 0..0.3 step 0.1 reach 0.3 despite rounding.
+    let count = length(((end - start) / step + 1e-9).floor() + 1.0)?;
+    Ok(Value::List(
+        (0..count)
+            .map(|i| Value::from(Quantity::from(start + i as f64 * step)))
+            .collect(),
+    ))
+}
+
+fn length(count: f64) -> Result<usize, String> {
+    match count {
+        count if count <= 0.0 => Ok(0),
+        count if count <= LIMIT as f64 => Ok(count as usize),
+        _ => Err(format!(
+            "Range too long: Expected at most {} values, got {}",
+            LIMIT, count
+        )),
+    }
+}
+
+// Items counted from 0, or from the end for negative indices.
+pub fn index(list: Value, index: Value) -> Result<Value, String> {
+    let Value::List(mut items) = list else {
+        return Err(format!("Unexpected '{}': Only lists can be indexed", list));
+    };
+
+    let i = index.integer()?.value;
+    let position = match i.is_negative() {
+        true => i.clone() + items.len(),
+        false => i.clone(),
+    };
+    match position
+        .to_usize()
+        .filter(|position| *position < items.len())
+    {
+        Some(position) => Ok(items.swap_remove(position)),
+        None => Err(format!(
+            "Index out of range: '{}' is outside a list of {} values",
+            i,
+            items.len()
+        )),
+    }
+}
+
+// Applies an operation to each item of a list.
+pub fn map(items: Vec<Value>, f: impl Fn(Value) -> Result<Value, String>) -> Result<Value, String> {
+    items
+        .into_iter()
+        .map(f)
+        .collect::<Result<_, _>>()
+        .map(Value::List)
+}
+
+// Applies an operation to items in the same positions of two lists.
+pub fn zip(
+    left: Vec<Value>,
+    right: Vec<Value>,
+    f: impl Fn(Value, Value) -> Result<Value, String>,
+) -> Result<Value, String> {
+    if left.len() != right.len() {
+        return Err(format!(
+            "Mismatched lists: Cannot combine a list of {} values with one of {}",
+            left.len(),
+            right.len()
+        ));
+    }
+    left.into_iter()
+        .zip(right)
+        .map(|(l, r)| f(l, r))
+        .collect::<Result<_, _>>()
+        .map(Value::List)
+}
+
+// Items of nested lists in order, for functions that take any number of values.
+pub fn flatten(values: Vec<Value>) -> Vec<Value> {
+    let mut flat = Vec::new();
+    for value in values {
+        match value {
+            Value::List(items) => flat.extend(flatten(items)),
+            value => flat.push(value),
+        }
+    }
+    flat
+}
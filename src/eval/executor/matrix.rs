@@ -0,0 +1,467 @@
+use num_bigint::{BigInt, BigUint};
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use crate::eval::{
+    executor::bin,
+    integer::Integer,
+    matrix::{
+        self, Matrix,
+        decompose::{self, cholesky, lu, qr},
+        eigen::{self, Complex},
+        is_matrix,
+    },
+    types::{Operator, Value},
+    unit::Quantity,
+};
+
+fn grid(items: Vec<Value>) -> Vec<Vec<Value>> {
+    items
+        .into_iter()
+        .map(|row| match row {
+            Value::List(row) => row,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+fn shape(items: &[Value]) -> String {
+    match items.first() {
+        Some(Value::List(row)) if is_matrix(items) => {
+            format!("a {}×{} matrix", items.len(), row.len())
+        }
+        _ => format!("a vector of {} values", items.len()),
+    }
+}
+
+fn mismatched(action: &str, l: &[Value], r: &[Value]) -> String {
+    format!(
+        "Mismatched dimensions: Cannot {} {} and {}",
+        action,
+        shape(l),
+        shape(r)
+    )
+}
+
+fn sum(values: impl Iterator<Item = Result<Value, String>>) -> Result<Value, String> {
+    let mut total: Option<Value> = None;
+    for value in values {
+        let value = value?;
+        total = Some(match total {
+            Some(total) => bin::values(total, Operator::Addition, value)?,
+            None => value,
+        });
+    }
+    total.ok_or("Not enough values: Expected at least one value to add".into())
+}
+
+// Matrix products, where a vector on the right is a column and one on the left is a row.
+pub fn multiply(l: Vec<Value>, r: Vec<Value>) -> Result<Value, String> {
+    let product =
+        |x: &Value, y: &Value| bin::values(x.clone(), Operator::Multiplication, y.clone());
+
+    match (is_matrix(&l), is_matrix(&r)) {
+        (true, true) => {
+            let (a, b) = (grid(l.clone()), grid(r.clone()));
+            if a[0].len() != b.len() {
+                return Err(mismatched("multiply", &l, &r));
+            }
+            let rows = a
+                .iter()
+                .map(|row| {
+                    let cells = (0..b[0].len())
+                        .map(|j| sum(row.iter().zip(b.iter()).map(|(x, b)| product(x, &b[j]))))
+                        .collect::<Result<_, _>>()?;
+                    Ok(Value::List(cells))
+                })
+                .collect::<Result<_, String>>()?;
+            Ok(Value::List(rows))
+        }
+        (true, false) => {
+            let a = grid(l.clone());
+            if a[0].len() != r.len() {
+                return Err(mismatched("multiply", &l, &r));
+            }
+            a.iter()
+                .map(|row| sum(row.iter().zip(r.iter()).map(|(x, y)| product(x, y))))
+                .collect::<Result<_, _>>()
+                .map(Value::List)
+        }
+        _ => {
+            let b = grid(r.clone());
+            if l.len() != b.len() {
+                return Err(mismatched("multiply", &l, &r));
+            }
+            (0..b[0].len())
+                .map(|j| sum(l.iter().zip(b.iter()).map(|(x, row)| product(x, &row[j]))))
+                .collect::<Result<_, _>>()
+                .map(Value::List)
+        }
+    }
+}
+
+fn list(id: &str, value: Value) -> Result<Vec<Value>, String> {
+    match value {
+        Value::List(items) if !items.is_empty() => Ok(items),
+        value => Err(format!(
+            "Unexpected '{}': Expected a vector or matrix for '{}'",
+            value, id
+        )),
+    }
+}
+
+// Whole powers of a square matrix by repeated squaring, with negative ones powers of its
+// inverse, so that a^2 is a * a and a^-1 is inv(a).
+pub fn power(items: Vec<Value>, exponent: Value) -> Result<Value, String> {
+    let a = grid(items.clone());
+    matrix::square("^", &a)?;
+    let shown = exponent.to_string();
+    let k = exponent
+        .integer()
+        .ok()
+        .filter(|k| k.value.magnitude() <= &BigUint::from(POWERS))
+        .ok_or(format!(
+            "Invalid exponent '{}': A matrix can only be raised to a whole power up to {}",
+            shown, POWERS
+        ))?
+        .value;
+
+    let int = |n: bool| Value::Int(Integer::fit(BigInt::from(u8::from(n))));
+    let mut result: Vec<Value> = (0..a.len())
+        .map(|i| Value::List((0..a.len()).map(|j| int(i == j)).collect()))
+        .collect();
+    let mut base = match k.is_negative() {
+        true => items_of(from_scalars(matrix::inverse(&scalars(&a)?)?)),
+        false => items,
+    };
+    let mut k = k.magnitude().clone();
+    while !k.is_zero() {
+        if k.bit(0) {
+            result = items_of(multiply(result, base.clone())?);
+        }
+        k >>= 1;
+        if !k.is_zero() {
+            base = items_of(multiply(base.clone(), base)?);
+        }
+    }
+    Ok(Value::List(result))
+}
+
+// The largest power a matrix is raised to, past which whole numbers grow too long to be useful.
+const POWERS: u32 = 10000;
+
+fn items_of(value: Value) -> Vec<Value> {
+    match value {
+        Value::List(rows) => rows,
+        _ => unreachable!(),
+    }
+}
+
+fn rows(id: &str, value: Value) -> Result<Vec<Vec<Value>>, String> {
+    let items = list(id, value)?;
+    match is_matrix(&items) {
+        true => Ok(grid(items)),
+        false => Err(format!(
+            "Unexpected {}: Expected a matrix for '{}'",
+            shape(&items),
+            id
+        )),
+    }
+}
+
+fn vector(id: &str, value: Value) -> Result<Vec<Value>, String> {
+    let items = list(id, value)?;
+    match items.iter().any(|x| matches!(x, Value::List(_))) {
+        true => Err(format!(
+            "Unexpected {}: Expected a vector for '{}'",
+            shape(&items),
+            id
+        )),
+        false => Ok(items),
+    }
+}
+
+pub fn scalars(rows: &[Vec<Value>]) -> Result<Matrix, String> {
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .map(|x| x.clone().quantity()?.scalar())
+                .collect::<Result<_, _>>()
+        })
+        .collect()
+}
+
+pub fn numbers(values: Vec<Value>) -> Result<Vec<f64>, String> {
+    values.into_iter().map(|x| x.quantity()?.scalar()).collect()
+}
+
+pub fn quantities(xs: &[f64]) -> Vec<Value> {
+    xs.iter().map(|&x| Value::from(Quantity::from(x))).collect()
+}
+
+pub fn from_scalars(m: Matrix) -> Value {
+    Value::List(
+        m.into_iter()
+            .map(|row| {
+                Value::List(
+                    row.into_iter()
+                        .map(|x| Value::from(Quantity::from(x)))
+                        .collect(),
+                )
+            })
+            .collect(),
+    )
+}
+
+// A real number, or text like 1 + 2i for a complex one.
+pub fn complex(z: Complex) -> Value {
+    let number = |x: f64| Value::from(Quantity::from(x));
+    match (z.re, z.im) {
+        (re, 0.0) => number(re),
+        (0.0, im) => Value::Text(format!("{}i", number(im))),
+        (re, im) => Value::Text(format!(
+            "{} {} {}i",
+            number(re),
+            if im < 0.0 { '-' } else { '+' },
+            number(im.abs())
+        )),
+    }
+}
+
+pub fn process(id: &str, mut values: Vec<Value>) -> Result<Value, String> {
+    match id {
+        "identity" => {
+            let n = values.remove(0).integer()?.value;
+            let Some(n) = n.to_usize().filter(|n| (1..=1000).contains(n)) else {
+                return Err(format!(
+                    "Invalid size '{}' for 'identity': Expected a whole number between 1 and 1000",
+                    n
+                ));
+            };
+            let int = |n: u8| Value::Int(Integer::fit(BigInt::from(n)));
+            Ok(Value::List(
+                (0..n)
+                    .map(|i| Value::List((0..n).map(|j| int(u8::from(i == j))).collect()))
+                    .collect(),
+            ))
+        }
+        "transpose" => {
+            let items = list(id, values.remove(0))?;
+            if !is_matrix(&items) {
+                return Ok(Value::List(
+                    items.into_iter().map(|x| Value::List(vec![x])).collect(),
+                ));
+            }
+            let a = grid(items);
+            Ok(Value::List(
+                (0..a[0].len())
+                    .map(|j| Value::List(a.iter().map(|row| row[j].clone()).collect()))
+                    .collect(),
+            ))
+        }
+        "det" => {
+            let a = rows(id, values.remove(0))?;
+            matrix::square(id, &a)?;
+
+            // Whole numbers stay exact.
+            if a.iter().flatten().all(|x| matches!(x, Value::Int(_))) {
+                let ints = a
+                    .into_iter()
+                    .map(|row| {
+                        row.into_iter()
+                            .map(|x| match x {
+                                Value::Int(i) => i.value,
+                                _ => unreachable!(),
+                            })
+                            .collect()
+                    })
+                    .collect();
+                return Ok(Value::Int(Integer::fit(matrix::det_exact(ints))));
+            }
+            Ok(Value::from(Quantity::from(matrix::det(&scalars(&a)?))))
+        }
+        "inv" => {
+            let a = scalars(&rows(id, values.remove(0))?)?;
+            matrix::square(id, &a)?;
+            Ok(from_scalars(matrix::inverse(&a)?))
+        }
+        "rank" => {
+            let a = scalars(&rows(id, values.remove(0))?)?;
+            let rank = BigInt::from(matrix::rank(&a));
+            Ok(Value::Int(Integer::fit(rank)))
+        }
+        "trace" => {
+            let a = rows(id, values.remove(0))?;
+            matrix::square(id, &a)?;
+            sum(a.iter().enumerate().map(|(i, row)| Ok(row[i].clone())))
+        }
+        "dot" => {
+            let b = vector(id, values.remove(1))?;
+            let a = vector(id, values.remove(0))?;
+            if a.len() != b.len() {
+                return Err(mismatched("take the dot product of", &a, &b));
+            }
+            sum(a
+                .into_iter()
+                .zip(b)
+                .map(|(x, y)| bin::values(x, Operator::Multiplication, y)))
+        }
+        "cross" => {
+            let b = vector(id, values.remove(1))?;
+            let a = vector(id, values.remove(0))?;
+            if a.len() != 3 || b.len() != 3 {
+                return Err(mismatched("take the cross product of", &a, &b));
+            }
+            let term = |i: usize, j: usize| {
+                let l = bin::values(a[i].clone(), Operator::Multiplication, b[j].clone())?;
+                let r = bin::values(a[j].clone(), Operator::Multiplication, b[i].clone())?;
+                bin::values(l, Operator::Subtraction, r)
+            };
+            Ok(Value::List(vec![term(1, 2)?, term(2, 0)?, term(0, 1)?]))
+        }
+        // The p-norm of a vector, 2 by default or the largest size for inf, or the Frobenius norm
+        // of a matrix.
+        "norm" => {
+            let p = match values.get(1) {
+                Some(p) => p.clone().quantity()?.scalar()?,
+                None => 2.0,
+            };
+            if p.is_nan() || p < 1.0 {
+                return Err(format!(
+                    "Invalid norm '{}': Expected a number of at least 1",
+                    p
+                ));
+            }
+
+            let with_p = values.len() > 1;
+            let items = list(id, values.remove(0))?;
+            let xs = match is_matrix(&items) {
+                true if with_p => {
+                    return Err("Unexpected argument: The norm of a matrix takes no 'p'".into());
+                }
+                true => grid(items).into_iter().flatten().collect(),
+                false => vector(id, Value::List(items))?,
+            };
+
+            let mut quantities = Vec::new();
+            for x in xs {
+                quantities.push(x.quantity()?);
+            }
+            for q in quantities.iter() {
+                quantities[0].compatible(q, "combine")?;
+            }
+            // Sizes are taken relative to the largest, whose powers can't overflow.
+            let largest = quantities.iter().map(|q| q.value.abs()).fold(0.0, f64::max);
+            let n = match p.is_infinite() || largest == 0.0 || largest.is_infinite() {
+                true => largest,
+                false => {
+                    let sum = quantities
+                        .iter()
+                        .map(|q| (q.value.abs() / largest).powf(p))
+                        .sum::<f64>();
+                    largest * sum.powf(1.0 / p)
+                }
+            };
+            Ok(Value::from(Quantity::new(n, quantities[0].dim.clone())))
+        }
+        "linsolve" => {
+            let b = vector(id, values.remove(1))?;
+            let a = rows(id, values.remove(0))?;
+            if a.len() != b.len() {
+                return Err(format!(
+                    "Mismatched dimensions: A {}×{} matrix needs a vector of {} values, not {}",
+                    a.len(),
+                    a[0].len(),
+                    a.len(),
+                    b.len()
+                ));
+            }
+            Ok(Value::List(quantities(&matrix::solve(
+                &scalars(&a)?,
+                &numbers(b)?,
+            )?)))
+        }
+        "lu" => {
+            let a = scalars(&rows(id, values.remove(0))?)?;
+            matrix::square(id, &a)?;
+            let (l, u, p) = lu(&a);
+            Ok(Value::List(vec![
+                from_scalars(l),
+                from_scalars(u),
+                from_scalars(p),
+            ]))
+        }
+        "qr" => {
+            let (q, r) = qr(&scalars(&rows(id, values.remove(0))?)?);
+            Ok(Value::List(vec![from_scalars(q), from_scalars(r)]))
+        }
+        "cholesky" => {
+            let a = scalars(&rows(id, values.remove(0))?)?;
+            matrix::square(id, &a)?;
+            Ok(from_scalars(cholesky(&a)?))
+        }
+        "svd" => {
+            let (u, s, v) = decompose::svd(&scalars(&rows(id, values.remove(0))?)?)?;
+            let s = (0..s.len())
+                .map(|i| {
+                    (0..s.len())
+                        .map(|j| if i == j { s[i] } else { 0.0 })
+                        .collect()
+                })
+                .collect();
+            Ok(Value::List(vec![
+                from_scalars(u),
+                from_scalars(s),
+                from_scalars(v),
+            ]))
+        }
+        // The eigenvalues, and a matrix with the matching eigenvectors as its columns.
+        "eig" => {
+            let a = scalars(&rows(id, values.remove(0))?)?;
+            matrix::square(id, &a)?;
+            let (values, vectors) = eigen::eig(&a)?;
+            let columns = (0..a.len())
+                .map(|i| Value::List(vectors.iter().map(|v| complex(v[i])).collect()))
+                .collect();
+            Ok(Value::List(vec![
+                Value::List(values.into_iter().map(complex).collect()),
+                Value::List(columns),
+            ]))
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval::session::eval;
+
+    #[test]
+    fn norm_of_large_and_infinite_p() {
+        assert_eq!(eval("norm([3, 4])"), "5");
+        assert_eq!(eval("norm([3, 4], 1)"), "7");
+        assert_eq!(eval("norm([3, 4], inf)"), "4");
+        assert_eq!(eval("norm([-3, 4], 1000)"), "4");
+        assert_eq!(eval("norm([0, 0])"), "0");
+        assert_eq!(eval("norm([3 m, 4 m], inf)"), "4 m");
+    }
+
+    #[test]
+    fn matrix_powers() {
+        assert_eq!(
+            eval("[[1, 2], [3, 4]]^2"),
+            eval("[[1, 2], [3, 4]] * [[1, 2], [3, 4]]")
+        );
+        assert_eq!(eval("[[1, 2], [3, 4]]^-1"), eval("inv([[1, 2], [3, 4]])"));
+        assert_eq!(eval("[[1, 2], [3, 4]]^0"), eval("identity(2)"));
+        assert_eq!(eval("[[1, 1], [1, 0]]^10"), "[[89, 55],\n [55, 34]]");
+        assert_eq!(eval("[1, 2, 3]^2"), "[1, 4, 9]");
+        assert_eq!(
+            eval("[[1, 2, 3], [4, 5, 6]]^2"),
+            "Mismatched dimensions: '^' needs a square matrix, not a 2×3 one"
+        );
+        assert_eq!(
+            eval("[[1, 2], [2, 4]]^-1"),
+            "Singular matrix: It has no inverse"
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use num_bigint::BigInt;
+use num_traits::Zero;
+
+use crate::eval::{
+    executor::{
+        self, bin, list,
+        matrix::{complex, numbers},
+    },
+    integer::Integer,
+    polynomial,
+    types::{Expr, Function, Operator, Value},
+};
+
+// Polynomials are lists of coefficients with the highest power first, so [1, -3, 2] is
+// x^2 - 3x + 2. Apart from roots, their arithmetic works on any values, keeping whole numbers
+// exact.
+pub fn process(id: &str, mut values: Vec<Value>) -> Result<Value, String> {
+    if id == "roots" {
+        let coefficients = match values.as_slice() {
+            [Value::Function(Function::Lambda(params, body))] if params.len() == 1 => {
+                let mut coefficients = expand(body, &params[0])?;
+                coefficients.reverse();
+                coefficients
+            }
+            [Value::List(_)] => numbers(coefficients(id, values.remove(0))?)?,
+            _ => numbers(values)?,
+        };
+        // The companion matrix of an infinite or undefined coefficient has no eigenvalues.
+        if let Some(c) = coefficients.iter().find(|c| !c.is_finite()) {
+            return Err(format!(
+                "Invalid coefficient '{}': 'roots' needs finite coefficients",
+                c
+            ));
+        }
+        let roots = polynomial::roots(&coefficients)?;
+        return Ok(Value::List(roots.into_iter().map(complex).collect()));
+    }
+
+    let p = coefficients(id, values.remove(0))?;
+    match id {
+        "polyval" => match values.remove(0) {
+            Value::List(xs) => list::map(xs, |x| evaluate(&p, x)),
+            x => evaluate(&p, x),
+        },
+        "polymul" => {
+            let q = coefficients(id, values.remove(0))?;
+            let mut product: Vec<Option<Value>> = vec![None; p.len() + q.len() - 1];
+            for (i, a) in p.iter().enumerate() {
+                for (j, b) in q.iter().enumerate() {
+                    let term = multiply(a, b)?;
+                    product[i + j] = Some(match product[i + j].take() {
+                        Some(sum) => bin::values(sum, Operator::Addition, term)?,
+                        None => term,
+                    });
+                }
+            }
+            Ok(Value::List(product.into_iter().flatten().collect()))
+        }
+        "polydiv" => {
+            let q = coefficients(id, values.remove(0))?;
+            let (quotient, remainder) = divide(p, q)?;
+            Ok(Value::List(vec![
+                Value::List(quotient),
+                Value::List(remainder),
+            ]))
+        }
+        "polyder" => {
+            let n = p.len() - 1;
+            if n == 0 {
+                return Ok(Value::List(vec![int(0)]));
+            }
+            let derivative = p[..n]
+                .iter()
+                .enumerate()
+                .map(|(i, c)| multiply(c, &int(n - i)))
+                .collect::<Result<_, _>>()?;
+            Ok(Value::List(derivative))
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn coefficients(id: &str, value: Value) -> Result<Vec<Value>, String> {
+    match value {
+        Value::List(items) if !items.is_empty() => Ok(items),
+        value => Err(format!(
+            "Unexpected '{}': Expected a list of coefficients like [1, -3, 2] for '{}'",
+            value, id
+        )),
+    }
+}
+
+fn int(n: usize) -> Value {
+    Value::Int(Integer::fit(BigInt::from(n)))
+}
+
+fn is_zero(value: &Value) -> bool {
+    match value {
+        Value::Int(i) => i.value.is_zero(),
+        Value::Quantity(q) => q.value == 0.0,
+        _ => false,
+    }
+}
+
+fn multiply(a: &Value, b: &Value) -> Result<Value, String> {
+    bin::values(a.clone(), Operator::Multiplication, b.clone())
+}
+
+// Horner's rule, which needs only one multiplication and addition per coefficient.
+fn evaluate(p: &[Value], x: Value) -> Result<Value, String> {
+    let mut value = p[0].clone();
+    for c in &p[1..] {
+        value = bin::values(multiply(&value, &x)?, Operator::Addition, c.clone())?;
+    }
+    Ok(value)
+}
+
+// Long division, giving a quotient and a remainder of lower degree than the divisor.
+fn divide(p: Vec<Value>, q: Vec<Value>) -> Result<(Vec<Value>, Vec<Value>), String> {
+    let Some(start) = q.iter().position(|c| !is_zero(c)) else {
+        return Err("Division by zero: Cannot divide by a polynomial that's zero".into());
+    };
+    let q = &q[start..];
+    if p.len() < q.len() {
+        return Ok((vec![int(0)], p));
+    }
+
+    let mut remainder = p;
+    let mut quotient = Vec::new();
+    for k in 0..=remainder.len() - q.len() {
+        let c = bin::values(remainder[k].clone(), Operator::Division, q[0].clone())?;
+        for (j, d) in q.iter().enumerate().skip(1) {
+            let term = multiply(&c, d)?;
+            remainder[k + j] = bin::values(remainder[k + j].clone(), Operator::Subtraction, term)?;
+        }
+        quotient.push(c);
+    }
+
+    let mut remainder = remainder.split_off(quotient.len());
+    let start = remainder.iter().position(|c| !is_zero(c));
+    remainder.drain(..start.unwrap_or(remainder.len()));
+    if remainder.is_empty() {
+        remainder.push(int(0));
+    }
+    Ok((quotient, remainder))
+}
+
+// The coefficients of an expression in x, lowest power first, read off its sums, products and
+// whole powers, with any part that doesn't involve x worked out as a constant.
+fn expand(expr: &Expr, x: &str) -> Result<Vec<f64>, String> {
+    if !mentions(expr, x) {
+        return Ok(vec![executor::calculate(expr)?.quantity()?.scalar()?]);
+    }
+    let constant = |expr: &Expr| executor::calculate(expr)?.quantity()?.scalar();
+
+    match expr {
+        Expr::Param(_) => Ok(vec![0.0, 1.0]),
+        Expr::Unary(Operator::Subtraction, side) => {
+            Ok(expand(side, x)?.into_iter().map(|c| -c).collect())
+        }
+        Expr::Bin(left, op @ (Operator::Addition | Operator::Subtraction), right) => {
+            let (mut a, mut b) = (expand(left, x)?, expand(right, x)?);
+            let n = a.len().max(b.len());
+            a.resize(n, 0.0);
+            b.resize(n, 0.0);
+            Ok(match op {
+                Operator::Addition => a.iter().zip(&b).map(|(a, b)| a + b).collect(),
+                _ => a.iter().zip(&b).map(|(a, b)| a - b).collect(),
+            })
+        }
+        Expr::Bin(left, Operator::Multiplication, right) => {
+            Ok(product(&expand(left, x)?, &expand(right, x)?))
+        }
+        Expr::Bin(left, Operator::Division, right) if !mentions(right, x) => {
+            let d = constant(right)?;
+            Ok(expand(left, x)?.into_iter().map(|c| c / d).collect())
+        }
+        Expr::Bin(base, Operator::Exponent, power) if !mentions(power, x) => {
+            let n = constant(power)?;
+            if n.fract() != 0.0 || !(0.0..=1000.0).contains(&n) {
+                return Err(format!(
+                    "Not a polynomial: '{}' needs a whole power from 0 to 1000",
+                    expr
+                ));
+            }
+            let base = expand(base, x)?;
+            Ok((0..n as usize).fold(vec![1.0], |p, _| product(&p, &base)))
+        }
+        _ => Err(format!(
+            "Not a polynomial: '{}' isn't made of sums, products and whole powers of {}",
+            expr, x
+        )),
+    }
+}
+
+fn product(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut p = vec![0.0; a.len() + b.len() - 1];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            p[i + j] += a * b;
+        }
+    }
+    p
+}
+
+fn mentions(expr: &Expr, x: &str) -> bool {
+    let any = |exprs: &[Expr]| exprs.iter().any(|expr| mentions(expr, x));
+    match expr {
+        Expr::Param(id) => id == x,
+        Expr::Var(_, value) | Expr::Unary(_, value) => mentions(value, x),
+        Expr::Bin(left, _, right) | Expr::Index(left, right) => {
+            mentions(left, x) || mentions(right, x)
+        }
+        Expr::Range(start, end, step) => {
+            mentions(start, x) || mentions(end, x) || step.as_ref().is_some_and(|s| mentions(s, x))
+        }
+        Expr::Func(_, args) | Expr::List(args) => any(args),
+        Expr::Call(callee, args) => mentions(callee, x) || any(args),
+        Expr::Lambda(params, body) => !params.iter().any(|p| p == x) && mentions(body, x),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval::session::eval;
+
+    #[test]
+    fn polydiv_gives_quotient_and_remainder() {
+        assert_eq!(eval("polydiv([1, -3, 2], [1, -1])"), "[[1, -2], [0]]");
+        assert_eq!(eval("polydiv([1, 0, 1], [1, 1])"), "[[1, -1], [2]]");
+        assert_eq!(eval("polydiv([1, 0, 0], [2, 1])"), "[[0.5, -0.25], [0.25]]");
+        assert_eq!(eval("polydiv([1, 2], [1, 0, 0])"), "[[0], [1, 2]]");
+        assert_eq!(
+            eval("polydiv([1, 2], [0, 0])"),
+            "Division by zero: Cannot divide by a polynomial that's zero"
+        );
+    }
+
+    #[test]
+    fn roots_of_coefficients_and_expressions() {
+        assert_eq!(eval("roots(1, -3, 2)"), "[2, 1]");
+        assert_eq!(eval("roots([1, 0, 1])"), "[1i, -1i]");
+        assert_eq!(eval("roots((x - 1)^3, x)"), "[1, 1, 1]");
+    }
+
+    #[test]
+    fn roots_rejects_coefficients_that_are_not_finite() {
+        assert_eq!(
+            eval("roots(1, 0/0)"),
+            "Invalid coefficient 'NaN': 'roots' needs finite coefficients"
+        );
+        assert_eq!(
+            eval("roots(x/0, x)"),
+            "Invalid coefficient 'inf': 'roots' needs finite coefficients"
+        );
+        assert_eq!(
+            eval("roots(1/0, 1)"),
+            "Invalid coefficient 'inf': 'roots' needs finite coefficients"
+        );
+    }
+}
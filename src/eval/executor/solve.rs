@@ -0,0 +1,116 @@
+use crate::eval::{
+    executor::{
+        function,
+        matrix::{numbers, quantities},
+    },
+    matrix, roots,
+    types::{Expr, Function, Value},
+    unit::Quantity,
+};
+
+// Equations arrive as a function of their unknowns giving each left side minus its right: a
+// list of them for a system, or a single value for one equation solved numerically, as for any
+// function of one value.
+pub fn process(mut values: Vec<Value>) -> Result<Value, String> {
+    match values.remove(0) {
+        Value::Function(Function::Lambda(unknowns, body)) if matches!(*body, Expr::List(_)) => {
+            system(unknowns, body)
+        }
+        Value::Function(function) => single(function, values.pop()),
+        value => Err(format!(
+            "Unexpected '{}': 'solve' takes equations like solve(2x + y = 3, x - y = 0) or a function",
+            value
+        )),
+    }
+}
+
+fn system(unknowns: Vec<String>, body: Box<Expr>) -> Result<Value, String> {
+    let n = unknowns.len();
+    let equations = Function::Lambda(unknowns.clone(), body);
+    // Each side is in base units, so that equations in units like x km = 500 m still balance.
+    let sides = |point: &[f64]| match function::call(&equations, quantities(point))? {
+        Value::List(sides) => sides
+            .into_iter()
+            .map(|side| Ok(side.quantity()?.value))
+            .collect::<Result<Vec<f64>, String>>(),
+        _ => unreachable!(),
+    };
+
+    // The sides at zero are the constants, and how they change along each unknown are the
+    // coefficients.
+    let constants = sides(&vec![0.0; n])?;
+    let mut a = vec![Vec::with_capacity(n); constants.len()];
+    for j in 0..n {
+        let mut point = vec![0.0; n];
+        point[j] = 1.0;
+        for ((row, side), c) in a.iter_mut().zip(sides(&point)?).zip(&constants) {
+            row.push(side - c);
+        }
+    }
+
+    // Another point off the axes catches equations that aren't linear, like x^2 = 4.
+    let point: Vec<f64> = (0..n).map(|j| 1.5 + 0.75 * j as f64).collect();
+    let linear = sides(&point)?
+        .iter()
+        .zip(&a)
+        .zip(&constants)
+        .all(|((side, row), c)| {
+            let expected = c + row.iter().zip(&point).map(|(a, x)| a * x).sum::<f64>();
+            (side - expected).abs() <= 1e-9 * side.abs().max(1.0)
+        });
+    if !linear || a.iter().flatten().chain(&constants).any(|x| !x.is_finite()) {
+        return Err(match n {
+            1 => format!(
+                "Nonlinear equation: Name the unknown to solve it numerically, as in solve(x^2 = 2, {})",
+                unknowns[0]
+            ),
+            _ => format!(
+                "Nonlinear system: 'solve' only handles equations that are linear in {}",
+                unknowns.join(", ")
+            ),
+        });
+    }
+
+    let b: Vec<f64> = constants.iter().map(|c| -c).collect();
+    let x = matrix::solve(&a, &b)?;
+    Ok(Value::Text(
+        unknowns
+            .iter()
+            .zip(x)
+            .map(|(name, x)| format!("{} = {}", name, Value::from(Quantity::from(x))))
+            .collect::<Vec<_>>()
+            .join(", "),
+    ))
+}
+
+// A root near a guess, 0 by default, or all the roots between two bounds given as a list.
+fn single(equation: Function, near: Option<Value>) -> Result<Value, String> {
+    let f = |x: f64| {
+        function::call(&equation, quantities(&[x]))?
+            .quantity()?
+            .scalar()
+    };
+
+    match near {
+        Some(Value::List(bounds)) => {
+            let [a, b] = numbers(bounds)?[..] else {
+                return Err("Invalid interval: Expected two bounds like [0, 10]".into());
+            };
+            let roots = roots::all(f, a.min(b), a.max(b))?;
+            Ok(Value::List(quantities(&roots)))
+        }
+        near => {
+            let guess = match near {
+                Some(guess) => guess.quantity()?.scalar()?,
+                None => 0.0,
+            };
+            match roots::near(f, guess)? {
+                Some(x) => Ok(Value::from(Quantity::from(x))),
+                None => Err(format!(
+                    "No root found: Could not find a root near {}",
+                    guess
+                )),
+            }
+        }
+    }
+}
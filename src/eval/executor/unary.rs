@@ -1,41 +1,138 @@
-use num_bigint::BigUint;
-use num_traits::{One, ToPrimitive, Zero};
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, Signed, ToPrimitive, Zero};
 
 use crate::eval::{
-    executor,
-    types::{Expr, Operator},
+    executor::{self, list},
+    integer::{self, Integer, Width},
+    special,
+    types::{Expr, Operator, Value},
+    unit::Quantity,
 };
 
-pub fn process(op: Operator, side: Box<Expr>) -> f64 {
-    let n = executor::calculate(*side);
+pub fn process(op: &Operator, side: &Expr) -> Result<Value, String> {
+    let side = match op {
+        Operator::Factorial(_) => integer::unreduced(|| executor::calculate(side))?,
+        _ => executor::calculate(side)?,
+    };
+    value(op.clone(), side)
+}
+
+fn value(op: Operator, value: Value) -> Result<Value, String> {
+    match value {
+        Value::List(items) => return list::map(items, |item| self::value(op.clone(), item)),
+        Value::Int(i) => return integer(op, i),
+        _ => {}
+    }
+
+    let q = match op {
+        Operator::BitNot => return integer(op, value.integer()?),
+        _ => value.quantity()?,
+    };
+    if !matches!(op, Operator::Radix(_)) {
+        q.absolute()?;
+    }
 
     match op {
-        Operator::Subtraction => -n,
+        Operator::Subtraction => Ok(Value::from(-q)),
         Operator::Factorial(amount) => {
-            if amount == 0 || n == 0.0 {
-                return 1.0;
+            let n = q.scalar()?;
+            if n < 0.0 && n.fract() == 0.0 {
+                return Err(format!(
+                    "Invalid factorial '({}){}': Not defined for negative whole numbers",
+                    n,
+                    "!".repeat(amount as usize)
+                ));
             }
 
-            let n_u128 = n as u128;
-            let amt_u128 = amount as u128;
-
-            let mut result = BigUint::one();
-            let mut i = BigUint::from(n_u128);
-
-            while i > BigUint::zero() {
-                result *= &i;
-
-                if &i <= &BigUint::from(amt_u128) {
-                    break;
+            // Other numbers follow the gamma function, so that 0.5! is sqrt(pi)/2.
+            if n.fract() != 0.0 {
+                if amount > 1 {
+                    return Err(format!(
+                        "Invalid factorial '{}{}': Multiple factorials need a whole number",
+                        n,
+                        "!".repeat(amount as usize)
+                    ));
                 }
+                return Ok(Value::from(Quantity::from(special::gamma(n + 1.0)?)));
+            }
 
-                i -= BigUint::from(amt_u128);
+            if amount == 0 || n == 0.0 {
+                return Ok(Value::from(Quantity::from(1.0)));
             }
 
             // Convert to f64 for return, with potential loss of precision
-            result.to_f64().unwrap_or(f64::INFINITY)
+            Ok(Value::from(Quantity::from(
+                factorial(BigUint::from(n as u128), amount)
+                    .to_f64()
+                    .unwrap_or(f64::INFINITY),
+            )))
         }
-        Operator::Absolute => n.abs(),
+        Operator::Percent => Ok(Value::from(q / Quantity::from(100.0))),
+        Operator::Radix(base) => Ok(Value::from(q.with_radix(base))),
+        Operator::Absolute => Ok(Value::from(Quantity::new(q.value.abs(), q.dim))),
         _ => unreachable!(),
     }
 }
+
+fn integer(op: Operator, i: Integer) -> Result<Value, String> {
+    let exact = match op {
+        Operator::Subtraction => -&i.value,
+        Operator::BitNot => !&i.value,
+        Operator::Absolute => i.value.abs(),
+        Operator::Factorial(amount) if !i.value.is_negative() => {
+            let n = i.value.magnitude().clone();
+            match amount == 0 || n.is_zero() {
+                true => BigInt::one(),
+                false => BigInt::from(factorial(n, amount)),
+            }
+        }
+        Operator::Radix(base) => return Ok(Value::Int(i.with_radix(base))),
+        op => return process(&op, &Expr::Num(i.to_f64())),
+    };
+
+    let mut result = match op {
+        Operator::Subtraction => Integer::reduce(exact),
+        _ => Integer::fit(exact),
+    };
+    // Inverting the bits of a value that fits always fits.
+    if let Operator::BitNot = op {
+        result.carry = false;
+        result.overflow = false;
+    }
+    result.carry |= i.carry;
+    result.overflow |= i.overflow;
+
+    // Negating reads the same bits as both signed and unsigned, like the binary operators.
+    if let (Operator::Subtraction, Some(width)) = (op, integer::width()) {
+        let unsigned = Width {
+            signed: false,
+            ..width
+        };
+        let signed = Width {
+            signed: true,
+            ..width
+        };
+        result.carry |= !unsigned.contains(&-unsigned.wrap(&i.value));
+        result.overflow |= !signed.contains(&-signed.wrap(&i.value));
+    }
+
+    Ok(Value::Int(result))
+}
+
+fn factorial(n: BigUint, amount: i8) -> BigUint {
+    let amount = BigUint::from(amount as u128);
+    let mut result = BigUint::one();
+    let mut i = n;
+
+    while i > BigUint::zero() {
+        result *= &i;
+
+        if i <= amount {
+            break;
+        }
+
+        i -= &amount;
+    }
+
+    result
+}
@@ -0,0 +1,226 @@
+use num_bigint::BigInt;
+
+// The IEEE-754 binary formats that `bits` can show a value in.
+#[derive(Debug, Clone, Copy)]
+struct Format {
+    bits: u32,
+    exponent: u32,
+    mantissa: u32,
+}
+
+impl Format {
+    fn new(bits: u32) -> Result<Self, String> {
+        match bits {
+            16 => Ok(Format {
+                bits,
+                exponent: 5,
+                mantissa: 10,
+            }),
+            32 => Ok(Format {
+                bits,
+                exponent: 8,
+                mantissa: 23,
+            }),
+            64 => Ok(Format {
+                bits,
+                exponent: 11,
+                mantissa: 52,
+            }),
+            _ => Err(format!(
+                "Invalid float width '{}': Expected 16, 32 or 64",
+                bits
+            )),
+        }
+    }
+
+    fn bias(&self) -> i64 {
+        (1 << (self.exponent - 1)) - 1
+    }
+
+    fn max_exponent(&self) -> u64 {
+        (1 << self.exponent) - 1
+    }
+
+    fn fields(&self, bits: u64) -> (u64, u64, u64) {
+        (
+            bits >> (self.bits - 1),
+            (bits >> self.mantissa) & self.max_exponent(),
+            bits & ((1 << self.mantissa) - 1),
+        )
+    }
+
+    fn encode(&self, value: f64) -> u64 {
+        match self.bits {
+            64 => value.to_bits(),
+            32 => (value as f32).to_bits() as u64,
+            _ => half(value),
+        }
+    }
+
+    fn decode(&self, bits: u64) -> f64 {
+        match self.bits {
+            64 => f64::from_bits(bits),
+            32 => f32::from_bits(bits as u32) as f64,
+            _ => {
+                let (sign, exponent, mantissa) = self.fields(bits);
+                let magnitude = match exponent {
+                    0 => mantissa as f64 * 2f64.powi(1 - self.bias() as i32 - self.mantissa as i32),
+                    e if e == self.max_exponent() && mantissa == 0 => f64::INFINITY,
+                    e if e == self.max_exponent() => f64::NAN,
+                    e => {
+                        (mantissa | 1 << self.mantissa) as f64
+                            * 2f64.powi(e as i32 - self.bias() as i32 - self.mantissa as i32)
+                    }
+                };
+                if sign == 1 { -magnitude } else { magnitude }
+            }
+        }
+    }
+}
+
+// Shows how a number is stored as a float of the given width: its fields, its bits in hex, its
+// exact decimal value and the spacing to its neighbours.
+pub fn inspect(value: f64, width: u32) -> Result<String, String> {
+    let format = Format::new(width)?;
+    let bits = format.encode(value);
+    let stored = format.decode(bits);
+    let (sign, exponent, mantissa) = format.fields(bits);
+
+    let class = match (exponent, mantissa) {
+        (0, 0) => "zero",
+        (0, _) => "subnormal",
+        (e, 0) if e == format.max_exponent() => "infinity",
+        (e, m) if e == format.max_exponent() && m >> (format.mantissa - 1) == 1 => "quiet NaN",
+        (e, _) if e == format.max_exponent() => "signalling NaN",
+        _ => "normal",
+    };
+
+    let mut lines = vec![format!("{:?} as f{}: {}", stored, format.bits, class)];
+    if !value.is_nan() && stored != value {
+        lines[0] += &format!(", rounded from {:?}", value);
+    }
+
+    lines.push(format!(
+        "sign      {} ({})",
+        sign,
+        if sign == 1 { '-' } else { '+' }
+    ));
+    let exponent_bits = format!("{:0width$b}", exponent, width = format.exponent as usize);
+    lines.push(match (exponent, class) {
+        (_, "zero") => format!("exponent  {} (zero)", exponent_bits),
+        (0, _) => format!(
+            "exponent  {} = 2^{} (subnormal)",
+            exponent_bits,
+            1 - format.bias()
+        ),
+        (_, "normal") => format!(
+            "exponent  {} = {} - {} = {}",
+            exponent_bits,
+            exponent,
+            format.bias(),
+            exponent as i64 - format.bias()
+        ),
+        _ => format!("exponent  {} (all ones)", exponent_bits),
+    });
+    lines.push(format!(
+        "mantissa  {:0width$b}",
+        mantissa,
+        width = format.mantissa as usize
+    ));
+    lines.push(format!(
+        "hex       0x{:0width$x}",
+        bits,
+        width = format.bits as usize / 4
+    ));
+
+    if stored.is_finite() {
+        lines.push(format!("exactly   {}", exact(stored)));
+
+        // Neighbours are found by stepping the bits, which orders floats of the same sign.
+        let magnitude = bits & !(1 << (format.bits - 1));
+        let sign = bits & (1 << (format.bits - 1));
+        let larger = format.decode(sign | (magnitude + 1));
+        let smaller = match magnitude {
+            0 => -format.decode(1 | sign),
+            _ => format.decode(sign | (magnitude - 1)),
+        };
+        let ulp = match larger.is_finite() {
+            true => (larger - stored).abs(),
+            false => (stored - smaller).abs(),
+        };
+        let (below, above) = match stored.is_sign_negative() {
+            true => (larger, smaller),
+            false => (smaller, larger),
+        };
+        lines.push(format!(
+            "ulp       {:?} (neighbours {:?} and {:?})",
+            ulp, below, above
+        ));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+// Rounds to the nearest half precision float, with ties to even, since there's no native type.
+fn half(value: f64) -> u64 {
+    let sign = (value.is_sign_negative() as u64) << 15;
+    if value.is_nan() {
+        return sign | 0x7e00;
+    }
+
+    let magnitude = value.abs();
+    if magnitude == 0.0 {
+        return sign;
+    }
+
+    let mut exponent = ((magnitude.to_bits() >> 52) as i64 & 0x7ff) - 1023;
+    if exponent < -14 {
+        // A rounded subnormal that reaches 0x400 is the smallest normal number, which is also
+        // the right encoding for it.
+        return sign | (magnitude * 2f64.powi(24)).round_ties_even() as u64;
+    }
+
+    let mut mantissa = (magnitude * 2f64.powi(10 - exponent as i32)).round_ties_even() as u64;
+    if mantissa == 2048 {
+        mantissa = 1024;
+        exponent += 1;
+    }
+    if exponent > 15 {
+        return sign | 0x7c00;
+    }
+
+    sign | ((exponent + 15) as u64) << 10 | (mantissa - 1024)
+}
+
+// Every finite float is a fraction with a power of two as its denominator, so it has an exact
+// decimal expansion.
+fn exact(value: f64) -> String {
+    if value == 0.0 {
+        return if value.is_sign_negative() { "-0" } else { "0" }.to_string();
+    }
+
+    let bits = value.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i64;
+    let mut mantissa = bits & ((1 << 52) - 1);
+    let mut shift = exponent - 1075;
+    match exponent {
+        0 => shift += 1,
+        _ => mantissa |= 1 << 52,
+    }
+
+    let sign = if value < 0.0 { "-" } else { "" };
+    if shift >= 0 {
+        return format!("{}{}", sign, BigInt::from(mantissa) << shift as usize);
+    }
+
+    // m / 2^k is the same as m * 5^k / 10^k.
+    let places = (-shift) as usize;
+    let digits = (BigInt::from(mantissa) * BigInt::from(5).pow(places as u32)).to_string();
+    let digits = format!("{:0>width$}", digits, width = places + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - places);
+    let fraction = fraction.trim_end_matches('0');
+    match fraction.is_empty() {
+        true => format!("{}{}", sign, whole),
+        false => format!("{}{}.{}", sign, whole, fraction),
+    }
+}
@@ -0,0 +1,222 @@
+use num_bigint::BigInt;
+use num_integer::Integer as _;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::eval::{environment, radix, types::Value};
+
+pub mod theory;
+
+// A fixed integer width set with `:int`, which results are wrapped to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Width {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+impl Width {
+    // Names like u8, i32 or u128.
+    pub fn named(name: &str) -> Option<Width> {
+        let signed = match name.chars().next()? {
+            'i' => true,
+            'u' => false,
+            _ => return None,
+        };
+
+        match name[1..].parse() {
+            Ok(bits @ (8 | 16 | 32 | 64 | 128)) => Some(Width { bits, signed }),
+            _ => None,
+        }
+    }
+
+    pub fn modulus(&self) -> BigInt {
+        BigInt::one() << self.bits
+    }
+
+    fn min(&self) -> BigInt {
+        match self.signed {
+            true => -(BigInt::one() << (self.bits - 1)),
+            false => BigInt::zero(),
+        }
+    }
+
+    fn max(&self) -> BigInt {
+        match self.signed {
+            true => (BigInt::one() << (self.bits - 1)) - 1,
+            false => self.modulus() - 1,
+        }
+    }
+
+    // The raw bits of a value as an unsigned number.
+    pub fn unsigned(&self, n: &BigInt) -> BigInt {
+        let modulus = self.modulus();
+        ((n % &modulus) + &modulus) % modulus
+    }
+
+    pub fn signed(&self, n: &BigInt) -> BigInt {
+        let n = self.unsigned(n);
+        match n >= BigInt::one() << (self.bits - 1) {
+            true => n - self.modulus(),
+            false => n,
+        }
+    }
+
+    pub fn wrap(&self, n: &BigInt) -> BigInt {
+        match self.signed {
+            true => self.signed(n),
+            false => self.unsigned(n),
+        }
+    }
+
+    pub fn contains(&self, n: &BigInt) -> bool {
+        *n >= self.min() && *n <= self.max()
+    }
+}
+
+impl std::fmt::Display for Width {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}{}", if self.signed { 'i' } else { 'u' }, self.bits)
+    }
+}
+
+// An exact integer. In a fixed width, `carry` records an unsigned result that wrapped and
+// `overflow` a signed one, and both stay set through the rest of the expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Integer {
+    pub value: BigInt,
+    pub radix: Option<u32>,
+    pub carry: bool,
+    pub overflow: bool,
+}
+
+impl Integer {
+    pub fn new(value: BigInt) -> Self {
+        Integer {
+            value,
+            radix: None,
+            carry: false,
+            overflow: false,
+        }
+    }
+
+    // Wraps a value to the session width if there is one, flagging it when it doesn't fit.
+    pub fn fit(value: BigInt) -> Self {
+        let Some(width) = width() else {
+            return Integer::new(value);
+        };
+
+        let wrapped = width.wrap(&value);
+        let changed = wrapped != value;
+        Integer {
+            value: wrapped,
+            radix: None,
+            carry: changed && !width.signed,
+            overflow: changed && width.signed,
+        }
+    }
+
+    // The result of a modular operation, reduced by the session modulus if there is one.
+    pub fn reduce(value: BigInt) -> Self {
+        Integer::fit(value).reduced()
+    }
+
+    pub fn reduced(mut self) -> Self {
+        if let Some(modulus) = modulus() {
+            self.value = self.value.mod_floor(&modulus);
+        }
+        self
+    }
+
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+
+        num_traits::FromPrimitive::from_f64(value).map(Integer::fit)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.value
+            .to_f64()
+            .unwrap_or(match self.value.is_negative() {
+                true => f64::NEG_INFINITY,
+                false => f64::INFINITY,
+            })
+    }
+
+    pub fn with_radix(mut self, base: u32) -> Self {
+        self.radix = Some(base);
+        self
+    }
+
+    // Carries the flags of both operands over to a result.
+    pub fn flags(mut self, l: &Integer, r: &Integer) -> Self {
+        self.carry |= l.carry || r.carry;
+        self.overflow |= l.overflow || r.overflow;
+        self
+    }
+}
+
+pub fn width() -> Option<Width> {
+    environment::fetch_settings().lock().unwrap().width
+}
+
+pub fn modulus() -> Option<BigInt> {
+    environment::fetch_settings()
+        .lock()
+        .unwrap()
+        .modulus
+        .clone()
+}
+
+// Runs a calculation with the session modulus switched off, for exponents, which count
+// repetitions rather than being residues themselves, and for the arguments of functions like
+// isprime, which need the number itself.
+pub fn unreduced<T>(calculate: impl FnOnce() -> T) -> T {
+    let modulus = environment::fetch_settings().lock().unwrap().modulus.take();
+    let result = calculate();
+    environment::fetch_settings().lock().unwrap().modulus = modulus;
+    result
+}
+
+// Integers in a final result are reduced by the session modulus, since functions and operators
+// like mod work on the numbers themselves.
+pub fn residues(value: Value) -> Value {
+    match value {
+        Value::Int(i) => Value::Int(i.reduced()),
+        Value::List(items) => Value::List(items.into_iter().map(residues).collect()),
+        value => value,
+    }
+}
+
+impl std::fmt::Display for Integer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let base = self
+            .radix
+            .unwrap_or_else(|| environment::fetch_settings().lock().unwrap().base);
+
+        match (base, width()) {
+            (10, _) => write!(f, "{}", self.value)?,
+            // With a fixed width, negative numbers show their two's complement bits.
+            (_, Some(width)) => write!(
+                f,
+                "{}{}",
+                radix::prefix(base),
+                width.unsigned(&self.value).to_str_radix(base)
+            )?,
+            (_, None) => write!(
+                f,
+                "{}{}{}",
+                if self.value.is_negative() { "-" } else { "" },
+                radix::prefix(base),
+                self.value.abs().to_str_radix(base)
+            )?,
+        }
+
+        match (self.carry, self.overflow) {
+            (true, true) => write!(f, " [carry, overflow]"),
+            (true, false) => write!(f, " [carry]"),
+            (false, true) => write!(f, " [overflow]"),
+            (false, false) => Ok(()),
+        }
+    }
+}
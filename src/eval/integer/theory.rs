@@ -0,0 +1,294 @@
+use num_bigint::BigInt;
+use num_integer::Integer as _;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+fn natural(id: &str, n: &BigInt) -> Result<(), String> {
+    match n.is_negative() {
+        true => Err(format!(
+            "Invalid argument '{}' for '{}': Expected a whole number of at least 0",
+            n, id
+        )),
+        false => Ok(()),
+    }
+}
+
+fn positive(id: &str, n: &BigInt) -> Result<(), String> {
+    match n.is_positive() {
+        true => Ok(()),
+        false => Err(format!(
+            "Invalid argument '{}' for '{}': Expected a whole number of at least 1",
+            n, id
+        )),
+    }
+}
+
+pub fn choose(n: &BigInt, r: &BigInt) -> Result<BigInt, String> {
+    natural("nCr", n)?;
+    natural("nCr", r)?;
+    if r > n {
+        return Ok(BigInt::zero());
+    }
+
+    // Each partial product is itself a binomial coefficient, so the division is exact.
+    let r = r.min(&(n - r)).clone();
+    let mut result = BigInt::one();
+    let mut i = BigInt::zero();
+    while i < r {
+        result = result * (n - &i) / (&i + 1);
+        i += 1;
+    }
+    Ok(result)
+}
+
+pub fn permutations(n: &BigInt, r: &BigInt) -> Result<BigInt, String> {
+    natural("nPr", n)?;
+    natural("nPr", r)?;
+    if r > n {
+        return Ok(BigInt::zero());
+    }
+
+    let mut result = BigInt::one();
+    let mut i = n - r + 1;
+    while &i <= n {
+        result *= &i;
+        i += 1;
+    }
+    Ok(result)
+}
+
+// Miller-Rabin with the first 13 primes as bases, which is exact below 3.3 * 10^24 and only
+// wrong for composites built to fool these bases above that.
+pub fn is_prime(n: &BigInt) -> bool {
+    const BASES: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+    if n < &BigInt::from(2) {
+        return false;
+    }
+    for base in BASES {
+        if *n == BigInt::from(base) {
+            return true;
+        }
+        if (n % base).is_zero() {
+            return false;
+        }
+    }
+
+    let one = BigInt::one();
+    let minus_one: BigInt = n - 1;
+    let twos = minus_one.trailing_zeros().unwrap_or(0);
+    let odd = &minus_one >> twos;
+
+    'bases: for base in BASES {
+        let mut x = BigInt::from(base).modpow(&odd, n);
+        if x == one || x == minus_one {
+            continue;
+        }
+        for _ in 1..twos {
+            x = &x * &x % n;
+            if x == minus_one {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+
+    true
+}
+
+pub fn next_prime(n: &BigInt) -> BigInt {
+    let mut candidate: BigInt = n + 1;
+    if candidate <= BigInt::from(2) {
+        return BigInt::from(2);
+    }
+    if candidate.is_even() {
+        candidate += 1;
+    }
+    while !is_prime(&candidate) {
+        candidate += 2;
+    }
+    candidate
+}
+
+// Prime factors with their powers in increasing order.
+pub fn factor(id: &str, n: &BigInt) -> Result<Vec<(BigInt, u32)>, String> {
+    positive(id, &n.abs())?;
+
+    let mut primes = Vec::new();
+    let mut n = n.abs();
+    for p in [2u32, 3, 5] {
+        while (&n % p).is_zero() {
+            primes.push(BigInt::from(p));
+            n /= p;
+        }
+    }
+
+    // Trial division with a wheel that skips multiples of 2, 3 and 5 takes out small factors,
+    // and Pollard's rho splits whatever is left.
+    let mut p = BigInt::from(7);
+    let wheel = [4u32, 2, 4, 2, 4, 6, 2, 6];
+    let mut i = 0;
+    while &p * &p <= n && p < BigInt::from(100_000) {
+        while (&n % &p).is_zero() {
+            primes.push(p.clone());
+            n /= &p;
+        }
+        p += wheel[i % 8];
+        i += 1;
+    }
+
+    let mut stack = vec![n];
+    while let Some(n) = stack.pop() {
+        if n.is_one() {
+            continue;
+        }
+        if is_prime(&n) {
+            primes.push(n);
+            continue;
+        }
+        let d = rho(&n).ok_or(format!(
+            "Could not factor '{}': Its prime factors are too large to find quickly",
+            n
+        ))?;
+        stack.push(&n / &d);
+        stack.push(d);
+    }
+
+    primes.sort();
+    let mut factors: Vec<(BigInt, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, power)) if *last == p => *power += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    Ok(factors)
+}
+
+// Brent's variant of Pollard's rho, which finds a non-trivial factor of a composite number. It
+// gives up after a few million steps, which is enough for factors of up to about 12 digits.
+fn rho(n: &BigInt) -> Option<BigInt> {
+    const BATCH: u64 = 128;
+    let mut budget: u64 = 1 << 20;
+
+    for c in 1u32.. {
+        let f = |x: &BigInt| (x * x + c) % n;
+        let mut y = BigInt::from(2);
+        let mut x = y.clone();
+        let mut ys = y.clone();
+        let mut q = BigInt::one();
+        let mut g = BigInt::one();
+        let mut r = 1;
+
+        while g.is_one() {
+            x = y.clone();
+            for _ in 0..r {
+                y = f(&y);
+            }
+
+            // Differences are multiplied together so that one gcd covers a whole batch.
+            let mut k = 0;
+            while k < r && g.is_one() {
+                ys = y.clone();
+                for _ in 0..BATCH.min(r - k) {
+                    y = f(&y);
+                    q = q * (&x - &y).abs() % n;
+                }
+                g = q.gcd(n);
+                k += BATCH;
+            }
+
+            budget = budget.checked_sub(2 * r)?;
+            r *= 2;
+        }
+
+        // The batch overshot, so step through it again one gcd at a time.
+        if &g == n {
+            loop {
+                ys = f(&ys);
+                g = (&x - &ys).abs().gcd(n);
+                if !g.is_one() {
+                    break;
+                }
+            }
+        }
+
+        if &g != n {
+            return Some(g);
+        }
+    }
+
+    None
+}
+
+pub fn totient(n: &BigInt) -> Result<BigInt, String> {
+    positive("totient", n)?;
+    let mut result = n.clone();
+    for (p, _) in factor("totient", n)? {
+        result = result / &p * (&p - 1);
+    }
+    Ok(result)
+}
+
+pub fn divisors(n: &BigInt) -> Result<Vec<BigInt>, String> {
+    positive("divisors", n)?;
+    let mut divisors = vec![BigInt::one()];
+    for (p, power) in factor("divisors", n)? {
+        let mut next = Vec::new();
+        for d in divisors.iter() {
+            let mut d = d.clone();
+            for _ in 0..=power {
+                next.push(d.clone());
+                d *= &p;
+            }
+        }
+        divisors = next;
+    }
+    divisors.sort();
+    Ok(divisors)
+}
+
+// Fast doubling, using F(2k) = F(k) (2F(k+1) - F(k)) and F(2k+1) = F(k)^2 + F(k+1)^2.
+pub fn fib(n: &BigInt) -> Result<BigInt, String> {
+    let Some(k) = n.abs().to_u64().filter(|k| *k <= 10_000_000) else {
+        return Err(format!(
+            "Invalid argument '{}' for 'fib': Expected a number between -10000000 and 10000000",
+            n
+        ));
+    };
+
+    let (mut a, mut b) = (BigInt::zero(), BigInt::one());
+    for bit in (0..64 - k.leading_zeros()).rev() {
+        let c = &a * (&b * 2 - &a);
+        let d = &a * &a + &b * &b;
+        (a, b) = match k >> bit & 1 {
+            1 => (d.clone(), c + d),
+            _ => (c, d),
+        };
+    }
+
+    // F(-k) = (-1)^(k+1) F(k)
+    match n.is_negative() && k % 2 == 0 {
+        true => Ok(-a),
+        false => Ok(a),
+    }
+}
+
+// The inverse of a modulo m, if a and m share no factors.
+pub fn inverse(a: &BigInt, m: &BigInt) -> Option<BigInt> {
+    let gcd = a.extended_gcd(m);
+    match gcd.gcd.is_one() {
+        true => Some(gcd.x.mod_floor(m)),
+        false => None,
+    }
+}
+
+pub fn modpow(base: &BigInt, exponent: &BigInt, m: &BigInt) -> Result<BigInt, String> {
+    positive("modpow", m)?;
+    let base = match exponent.is_negative() {
+        true => inverse(base, m).ok_or(format!(
+            "Invalid argument '{}' for 'modpow': Has no inverse modulo {} for a negative exponent",
+            base, m
+        ))?,
+        false => base.mod_floor(m),
+    };
+    Ok(base.modpow(&exponent.abs(), m))
+}
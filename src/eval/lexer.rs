@@ -1,59 +1,149 @@
-use crate::eval::types::Token;
+use crate::eval::{parser::prefix::ident::builtin, types::Token};
 use std::{iter::Peekable, str::Chars};
 
+pub mod date;
+pub mod dice;
+pub mod radix;
+
 pub fn lex(expr: &mut Peekable<Chars>) -> Result<Vec<Token>, String> {
     let mut tokens = Vec::new();
 
-    while let Some(c) = expr.peek() {
+    while let Some(&c) = expr.peek() {
         match c {
+            '.' if expr.clone().nth(1) == Some('.') => {
+                tokens.push(Token::DotDot);
+                expr.nth(1);
+            }
             '0'..='9' | '.' => {
+                if let Some(token) = date::lex(expr)? {
+                    tokens.push(token);
+                    continue;
+                }
+
+                if let Some(token) = radix::lex(expr)? {
+                    tokens.push(token);
+                    continue;
+                }
+
+                if let Some(token) = dice::lex(expr)? {
+                    tokens.push(token);
+                    continue;
+                }
+
                 let mut num = String::new();
-                while let Some(c) = expr.peek() {
-                    if c.is_digit(10) || c == &'.' {
-                        num.push(*c);
+                while let Some(&c) = expr.peek() {
+                    // A second '.' starts a range, as in 1..10.
+                    if c == '.' && expr.clone().nth(1) == Some('.') {
+                        break;
+                    }
+                    if c.is_ascii_digit() || c == '.' {
+                        num.push(c);
                     } else {
                         break;
                     }
                     expr.next();
                 }
-                tokens.push(Token::Num(
-                    num.parse::<f64>()
-                        .expect("Failed to parse a string into a number"),
-                ));
+                if !num.contains('.') {
+                    tokens.push(Token::Int(crate::eval::radix::parse_int(&num, 10)?));
+                } else {
+                    tokens.push(Token::Num(num.parse::<f64>().map_err(|_| {
+                        format!(
+                            "Invalid number '{}': Expected digits with at most one '.'",
+                            num
+                        )
+                    })?));
+                }
+
+                // Written right after a number, as in 5ft11in, 'in' is inches rather than a
+                // conversion.
+                let mut ahead = expr.clone();
+                if ahead.next() == Some('i')
+                    && ahead.next() == Some('n')
+                    && !ahead.next().is_some_and(char::is_alphanumeric)
+                {
+                    tokens.push(Token::Identifier("inch".to_string()));
+                    expr.nth(1);
+                }
             }
-            'a'..='z' | 'A'..='Z' => {
+            c if c.is_alphabetic() || c == '°' => {
+                if let Some(token) = dice::lex(expr)? {
+                    tokens.push(token);
+                    continue;
+                }
+
                 let mut identifier = String::new();
                 while let Some(c) = expr.peek() {
-                    if c.is_alphabetic() {
+                    if c.is_alphabetic() || c == &'°' {
                         identifier.push(*c);
+                    } else if c.is_ascii_digit() {
+                        // Digits only belong to function names like 'chi2cdf', so that '1m20cm'
+                        // is still two quantities and 'm2' a unit followed by a number.
+                        let mut ahead = expr.clone();
+                        let digits: String =
+                            std::iter::from_fn(|| ahead.next_if(char::is_ascii_digit)).collect();
+                        let rest: String =
+                            std::iter::from_fn(|| ahead.next_if(|c| c.is_alphabetic())).collect();
+                        if !builtin(&format!("{}{}{}", identifier, digits, rest)) {
+                            break;
+                        }
+                        identifier.push_str(&digits);
+                        expr.nth(digits.len() - 1);
+                        continue;
                     } else {
                         break;
                     }
                     expr.next();
                 }
 
-                tokens.push(Token::Identifier(identifier));
+                match identifier.as_str() {
+                    "to" | "in" => tokens.push(Token::To),
+                    "xor" => tokens.push(Token::Xor),
+                    "mod" => tokens.push(Token::Mod),
+                    "rem" => tokens.push(Token::Rem),
+                    "of" => tokens.push(Token::Of),
+                    "as" => tokens.push(Token::As),
+                    "step" => tokens.push(Token::Step),
+                    _ => tokens.push(Token::Identifier(identifier)),
+                }
             }
             '+' => {
                 tokens.push(Token::Plus);
                 expr.next();
             }
             '-' => {
-                tokens.push(Token::Minus);
                 expr.next();
+                if expr.peek() == Some(&'>') {
+                    tokens.push(Token::Arrow);
+                    expr.next();
+                } else {
+                    tokens.push(Token::Minus);
+                }
             }
             '*' => {
                 tokens.push(Token::Star);
                 expr.next();
             }
             '/' => {
-                tokens.push(Token::Slash);
                 expr.next();
+                if expr.peek() == Some(&'/') {
+                    tokens.push(Token::DoubleSlash);
+                    expr.next();
+                } else {
+                    tokens.push(Token::Slash);
+                }
             }
             '^' => {
                 tokens.push(Token::Carrot);
                 expr.next();
             }
+            '[' => {
+                tokens.push(Token::LeftBracket);
+                expr.next();
+            }
+            ']' => {
+                tokens.push(Token::RightBracket);
+                expr.next();
+            }
             '(' => {
                 tokens.push(Token::LeftParen);
                 expr.next();
@@ -86,6 +176,28 @@ pub fn lex(expr: &mut Peekable<Chars>) -> Result<Vec<Token>, String> {
                 tokens.push(Token::Equal);
                 expr.next();
             }
+            '&' => {
+                tokens.push(Token::Ampersand);
+                expr.next();
+            }
+            '~' => {
+                tokens.push(Token::Tilde);
+                expr.next();
+            }
+            '<' | '>' => {
+                expr.next();
+                if expr.next() != Some(c) {
+                    return Err(format!(
+                        "Unknown token '{}': Expected '{}{}' for a bit shift",
+                        c, c, c
+                    ));
+                }
+
+                tokens.push(match c {
+                    '<' => Token::ShiftLeft,
+                    _ => Token::ShiftRight,
+                });
+            }
             ' ' | '\t' | '\n' => {
                 expr.next();
             }
@@ -0,0 +1,188 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::eval::{date::Date, types::Token};
+
+// Reads a date like 2026-10-18, optionally followed by a time and a UTC offset as in
+// 2026-10-18T14:30:00+02:00, or a time of day like 14:30 which becomes a duration since midnight,
+// so 14:30 is 52200 s.
+// The iterator is only advanced when a literal is found.
+pub fn lex(expr: &mut Peekable<Chars>) -> Result<Option<Token>, String> {
+    let mut chars = expr.clone();
+    let Some(first) = digits(&mut chars, 1, 4) else {
+        return Ok(None);
+    };
+
+    let token = match chars.peek() {
+        Some('-') if first.1 == 4 => {
+            chars.next();
+            let Some((month, 2)) = digits(&mut chars, 2, 2) else {
+                return Ok(None);
+            };
+            if chars.next() != Some('-') {
+                return Ok(None);
+            }
+            let Some((day, 2)) = digits(&mut chars, 2, 2) else {
+                return Ok(None);
+            };
+
+            let mut time = 0.0;
+            let mut offset = 0;
+            let mut after = chars.clone();
+            if matches!(after.next(), Some('T' | ' '))
+                && let Some(t) = self::time(&mut after)?
+            {
+                time = t;
+                chars = after;
+                offset = self::offset(&mut chars).unwrap_or(0);
+            }
+
+            let date = Date::from_civil(first.0, month, day, time, offset).ok_or(format!(
+                "Invalid date '{:04}-{:02}-{:02}': Expected a real calendar day",
+                first.0, month, day
+            ))?;
+            Token::Date(date)
+        }
+        Some(':') if first.1 <= 2 => {
+            let mut chars_time = expr.clone();
+            let Some(time) = self::time(&mut chars_time)? else {
+                return Ok(None);
+            };
+            chars = chars_time;
+            Token::Time(time)
+        }
+        _ => return Ok(None),
+    };
+
+    if chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+        return Ok(None);
+    }
+
+    *expr = chars;
+    Ok(Some(token))
+}
+
+// Seconds since midnight for hh:mm or hh:mm:ss with an optional fraction of a second, or an
+// error for one shaped like a time that isn't on a clock, like 25:00.
+fn time(chars: &mut Peekable<Chars>) -> Result<Option<f64>, String> {
+    let Some((hours, _)) = digits(chars, 1, 2) else {
+        return Ok(None);
+    };
+    if chars.next() != Some(':') {
+        return Ok(None);
+    }
+    let Some((minutes, 2)) = digits(chars, 2, 2) else {
+        return Ok(None);
+    };
+    let mut text = format!("{}:{:02}", hours, minutes);
+
+    let mut seconds = 0.0;
+    if chars.peek() == Some(&':') {
+        chars.next();
+        let Some((whole, 2)) = digits(chars, 2, 2) else {
+            return Ok(None);
+        };
+        seconds = whole as f64;
+        text += &format!(":{:02}", whole);
+
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            let mut fraction = String::from("0.");
+            while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                fraction.push(*c);
+                chars.next();
+            }
+            seconds += fraction.parse::<f64>().unwrap_or(0.0);
+            text += &fraction[1..];
+        }
+    }
+
+    // 24:00 is the end of the day, and a 60th second allows for a leap second.
+    if hours > 24
+        || minutes > 59
+        || seconds >= 61.0
+        || (hours == 24 && (minutes > 0 || seconds > 0.0))
+    {
+        return Err(format!(
+            "Invalid time '{}': Expected a time of day from 0:00 to 24:00",
+            text
+        ));
+    }
+
+    Ok(Some((hours * 3600 + minutes * 60) as f64 + seconds))
+}
+
+// Offset in seconds for Z, +hh:mm or -hh:mm.
+fn offset(chars: &mut Peekable<Chars>) -> Option<i64> {
+    let sign = match chars.peek() {
+        Some('Z') => {
+            chars.next();
+            return Some(0);
+        }
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return None,
+    };
+
+    let mut after = chars.clone();
+    after.next();
+    let (hours, 2) = digits(&mut after, 2, 2)? else {
+        return None;
+    };
+    if after.next() != Some(':') {
+        return None;
+    }
+    let (minutes, 2) = digits(&mut after, 2, 2)? else {
+        return None;
+    };
+
+    *chars = after;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+// Reads between min and max ASCII digits, returning the number and how many digits were read.
+fn digits(chars: &mut Peekable<Chars>, min: usize, max: usize) -> Option<(i64, usize)> {
+    let mut s = String::new();
+    while s.len() < max
+        && let Some(c) = chars.peek().filter(|c| c.is_ascii_digit())
+    {
+        s.push(*c);
+        chars.next();
+    }
+
+    if s.len() < min {
+        return None;
+    }
+
+    Some((s.parse().ok()?, s.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lex;
+    use crate::eval::types::Token;
+
+    fn time(text: &str) -> Result<Option<Token>, String> {
+        lex(&mut text.chars().peekable())
+    }
+
+    #[test]
+    fn times_of_day_are_seconds_since_midnight() {
+        assert_eq!(time("14:30"), Ok(Some(Token::Time(52200.0))));
+        assert_eq!(time("9:05:30.5"), Ok(Some(Token::Time(32730.5))));
+        assert_eq!(time("24:00"), Ok(Some(Token::Time(86400.0))));
+    }
+
+    #[test]
+    fn times_off_the_clock_are_rejected() {
+        for text in ["25:00", "12:75", "1:30:70", "24:30"] {
+            assert_eq!(
+                time(text),
+                Err(format!(
+                    "Invalid time '{}': Expected a time of day from 0:00 to 24:00",
+                    text
+                ))
+            );
+        }
+        assert_eq!(time("12:5"), Ok(None));
+    }
+}
@@ -0,0 +1,78 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::eval::{
+    random::{Dice, Keep},
+    types::Token,
+};
+
+// Reads dice written as 3d6, d20, or 4d6kh3 and 2d20kl1 to keep the highest or lowest rolls. The
+// iterator is only advanced when dice are found.
+pub fn lex(expr: &mut Peekable<Chars>) -> Result<Option<Token>, String> {
+    let mut chars = expr.clone();
+    let count = digits(&mut chars);
+    if chars.next() != Some('d') {
+        return Ok(None);
+    }
+    let sides = digits(&mut chars);
+    if sides.is_empty() {
+        return Ok(None);
+    }
+
+    let mut ahead = chars.clone();
+    let keep = match (ahead.next(), ahead.next()) {
+        (Some('k'), Some(side @ ('h' | 'l'))) => {
+            chars.nth(1);
+            let n = digits(&mut chars);
+            let n = number(&n, "kept dice")?;
+            Some(match side {
+                'h' => Keep::Highest(n),
+                _ => Keep::Lowest(n),
+            })
+        }
+        _ => None,
+    };
+
+    // Anything else running on, like 2d6m, isn't dice.
+    if chars.peek().is_some_and(|c| c.is_alphanumeric()) {
+        return Ok(None);
+    }
+
+    let dice = Dice {
+        count: match count.is_empty() {
+            true => 1,
+            false => number(&count, "dice")?,
+        },
+        sides: number(&sides, "sides")?,
+        keep,
+    };
+    if let Some(Keep::Highest(n) | Keep::Lowest(n)) = dice.keep
+        && n > dice.count
+    {
+        return Err(format!(
+            "Invalid dice '{}': Cannot keep more dice than are rolled",
+            dice
+        ));
+    }
+
+    *expr = chars;
+    Ok(Some(Token::Dice(dice)))
+}
+
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        digits.push(c);
+    }
+    digits
+}
+
+fn number(digits: &str, name: &str) -> Result<u32, String> {
+    digits
+        .parse()
+        .ok()
+        .filter(|n| (1..=1_000_000).contains(n))
+        .ok_or(format!(
+            "Invalid number of {} '{}': Expected a number between 1 and 1000000",
+            name, digits
+        ))
+}
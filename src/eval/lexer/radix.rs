@@ -0,0 +1,44 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::eval::{radix, types::Token};
+
+// Reads integers and fractions written as 0xff, 0o755 or 0b1010, or in any base from 2 to 36
+// as in 36#zz. The iterator is only advanced when a literal is found.
+pub fn lex(expr: &mut Peekable<Chars>) -> Result<Option<Token>, String> {
+    let mut chars = expr.clone();
+    let base = match (chars.next(), chars.next()) {
+        (Some('0'), Some('x' | 'X')) => 16,
+        (Some('0'), Some('o' | 'O')) => 8,
+        (Some('0'), Some('b' | 'B')) => 2,
+        _ => {
+            chars = expr.clone();
+            let mut base = String::new();
+            while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                base.push(*c);
+                chars.next();
+            }
+
+            if chars.next() != Some('#') {
+                return Ok(None);
+            }
+
+            base.parse()
+                .map_err(|_| format!("Invalid base '{}': Expected a base between 2 and 36", base))?
+        }
+    };
+
+    let mut digits = String::new();
+    while let Some(c) = chars
+        .peek()
+        .filter(|c| c.is_ascii_alphanumeric() || **c == '.')
+    {
+        digits.push(*c);
+        chars.next();
+    }
+
+    *expr = chars;
+    match digits.contains('.') {
+        true => Ok(Some(Token::Num(radix::parse(&digits, base)?))),
+        false => Ok(Some(Token::Int(radix::parse_int(&digits, base)?))),
+    }
+}
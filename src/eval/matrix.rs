@@ -0,0 +1,287 @@
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+
+use crate::eval::types::Value;
+
+pub mod decompose;
+pub mod eigen;
+
+// A list of equally long lists of values is a matrix, with the inner lists as its rows.
+pub fn is_matrix(items: &[Value]) -> bool {
+    let Some(Value::List(first)) = items.first() else {
+        return false;
+    };
+    !first.is_empty()
+        && items.iter().all(|row| match row {
+            Value::List(row) => {
+                row.len() == first.len() && !row.iter().any(|x| matches!(x, Value::List(_)))
+            }
+            _ => false,
+        })
+}
+
+// Rows of a matrix of plain numbers, for the algorithms that need floating point.
+pub type Matrix = Vec<Vec<f64>>;
+
+pub fn identity(n: usize) -> Matrix {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect()
+}
+
+pub fn square<T>(id: &str, a: &[Vec<T>]) -> Result<(), String> {
+    match a.len() == a[0].len() {
+        true => Ok(()),
+        false => Err(format!(
+            "Mismatched dimensions: '{}' needs a square matrix, not a {}×{} one",
+            id,
+            a.len(),
+            a[0].len()
+        )),
+    }
+}
+
+// The largest magnitude in a matrix, which scales the tolerance for treating values as zero.
+fn scale(a: &Matrix) -> f64 {
+    a.iter().flatten().fold(0.0, |max, x| x.abs().max(max))
+}
+
+// The determinant of a whole-number matrix, kept exact with Bareiss's fraction-free elimination,
+// whose divisions always come out even.
+pub fn det_exact(mut a: Vec<Vec<BigInt>>) -> BigInt {
+    let n = a.len();
+    let mut sign = BigInt::one();
+    let mut previous = BigInt::one();
+    for k in 0..n {
+        if a[k][k].is_zero() {
+            let Some(swap) = (k + 1..n).find(|&i| !a[i][k].is_zero()) else {
+                return BigInt::zero();
+            };
+            a.swap(k, swap);
+            sign = -sign;
+        }
+        for i in k + 1..n {
+            for j in k + 1..n {
+                a[i][j] = (&a[i][j] * &a[k][k] - &a[i][k] * &a[k][j]) / &previous;
+            }
+        }
+        previous = a[k][k].clone();
+    }
+    sign * &a[n - 1][n - 1]
+}
+
+// Gaussian elimination with partial pivoting, which keeps the rounding errors small.
+pub fn det(a: &Matrix) -> f64 {
+    let mut a = a.clone();
+    let n = a.len();
+    let mut det = 1.0;
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&i, &j| a[i][k].abs().total_cmp(&a[j][k].abs()))
+            .unwrap();
+        if a[pivot][k] == 0.0 {
+            return 0.0;
+        }
+        if pivot != k {
+            a.swap(pivot, k);
+            det = -det;
+        }
+        det *= a[k][k];
+        for i in k + 1..n {
+            let factor = a[i][k] / a[k][k];
+            eliminate(&mut a, k, i, factor, k);
+        }
+    }
+    det
+}
+
+fn transpose(a: &Matrix) -> Matrix {
+    (0..a[0].len())
+        .map(|j| a.iter().map(|row| row[j]).collect())
+        .collect()
+}
+
+// Whether a matrix equals its transpose, up to rounding errors.
+fn symmetric(a: &Matrix) -> bool {
+    let tolerance = scale(a) * a.len() as f64 * f64::EPSILON;
+    a.iter().enumerate().all(|(i, row)| {
+        row[..i]
+            .iter()
+            .enumerate()
+            .all(|(j, x)| (x - a[j][i]).abs() <= tolerance)
+    })
+}
+
+// Subtracts a multiple of the pivot row from a later row i, from a column on.
+fn eliminate(a: &mut Matrix, pivot: usize, i: usize, factor: f64, from: usize) {
+    let (above, below) = a.split_at_mut(i);
+    for (x, p) in below[0][from..].iter_mut().zip(&above[pivot][from..]) {
+        *x -= factor * p;
+    }
+}
+
+// Gauss-Jordan elimination on the matrix beside the identity.
+pub fn inverse(a: &Matrix) -> Result<Matrix, String> {
+    let n = a.len();
+    let tolerance = scale(a) * n as f64 * f64::EPSILON;
+    let mut a = a.clone();
+    let mut inverse = identity(n);
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&i, &j| a[i][k].abs().total_cmp(&a[j][k].abs()))
+            .unwrap();
+        if a[pivot][k].abs() <= tolerance {
+            return Err("Singular matrix: It has no inverse".into());
+        }
+        a.swap(pivot, k);
+        inverse.swap(pivot, k);
+
+        let p = a[k][k];
+        for j in 0..n {
+            a[k][j] /= p;
+            inverse[k][j] /= p;
+        }
+        for i in (0..n).filter(|&i| i != k) {
+            let factor = a[i][k];
+            for j in 0..n {
+                a[i][j] -= factor * a[k][j];
+                inverse[i][j] -= factor * inverse[k][j];
+            }
+        }
+    }
+    Ok(inverse)
+}
+
+// The number of independent rows, counting pivots that aren't lost in rounding.
+pub fn rank(a: &Matrix) -> usize {
+    let (rows, cols) = (a.len(), a[0].len());
+    let tolerance = scale(a) * rows.max(cols) as f64 * f64::EPSILON;
+    let mut a = a.clone();
+    let mut rank = 0;
+    for k in 0..cols {
+        if rank == rows {
+            break;
+        }
+        let pivot = (rank..rows)
+            .max_by(|&i, &j| a[i][k].abs().total_cmp(&a[j][k].abs()))
+            .unwrap();
+        if a[pivot][k].abs() <= tolerance {
+            continue;
+        }
+        a.swap(pivot, rank);
+        for i in rank + 1..rows {
+            let factor = a[i][k] / a[rank][k];
+            eliminate(&mut a, rank, i, factor, k);
+        }
+        rank += 1;
+    }
+    rank
+}
+
+// Solves a x = b by Gaussian elimination with partial pivoting on the augmented matrix, failing
+// rather than dividing by a pivot lost in rounding.
+pub fn solve(a: &Matrix, b: &[f64]) -> Result<Vec<f64>, String> {
+    let (rows, cols) = (a.len(), a[0].len());
+    let mut a: Matrix = a
+        .iter()
+        .zip(b)
+        .map(|(row, b)| row.iter().chain([b]).copied().collect())
+        .collect();
+    let tolerance = scale(&a) * rows.max(cols) as f64 * f64::EPSILON;
+    let mut rank = 0;
+    for k in 0..cols {
+        if rank == rows {
+            break;
+        }
+        let pivot = (rank..rows)
+            .max_by(|&i, &j| a[i][k].abs().total_cmp(&a[j][k].abs()))
+            .unwrap();
+        if a[pivot][k].abs() <= tolerance {
+            continue;
+        }
+        a.swap(pivot, rank);
+        for i in rank + 1..rows {
+            let factor = a[i][k] / a[rank][k];
+            eliminate(&mut a, rank, i, factor, k);
+        }
+        rank += 1;
+    }
+
+    if a[rank..].iter().any(|row| row[cols].abs() > tolerance) {
+        return Err("Inconsistent system: The equations contradict each other".into());
+    }
+    if rank < cols && rows < cols {
+        return Err(format!(
+            "Underdetermined system: Expected {} equations for {} unknowns, got {}",
+            cols, cols, rows
+        ));
+    }
+    if rank < cols {
+        return Err("Singular system: The equations have no unique solution".into());
+    }
+
+    let mut x = vec![0.0; cols];
+    for k in (0..cols).rev() {
+        let known: f64 = (k + 1..cols).map(|j| a[k][j] * x[j]).sum();
+        // Adding zero turns -0 into 0.
+        x[k] = (a[k][cols] - known) / a[k][k] + 0.0;
+    }
+    Ok(x)
+}
+
+// The most sweeps the Jacobi methods make before giving up, far more than they ever need.
+const SWEEPS: usize = 100;
+
+// Replaces rows p and q of a with their rotation by the angle whose cosine is c and sine is s.
+fn rotate(a: &mut Matrix, p: usize, q: usize, c: f64, s: f64) {
+    let (above, below) = a.split_at_mut(q);
+    for (x, y) in above[p].iter_mut().zip(below[0].iter_mut()) {
+        (*x, *y) = (c * *x - s * *y, s * *x + c * *y);
+    }
+}
+
+// The component of largest size, whose sign decides which way a vector points.
+fn leading(v: &[f64]) -> f64 {
+    let max = v.iter().fold(0.0, |max: f64, x| max.max(x.abs()));
+    *v.iter()
+        .find(|x| x.abs() >= max * (1.0 - 1e-9))
+        .unwrap_or(&0.0)
+}
+
+// The direction of the reflection that takes x onto its first axis, with its squared length, or
+// None when x is already zero.
+fn householder(x: &[f64]) -> Option<(Vec<f64>, f64)> {
+    let norm = x.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm == 0.0 {
+        return None;
+    }
+    let mut v = x.to_vec();
+    v[0] += norm.copysign(x[0]);
+    let length = v.iter().map(|x| x * x).sum();
+    Some((v, length))
+}
+
+// Reflects the rows of a from a row on, as in H a.
+fn reflect_rows(a: &mut Matrix, v: &[f64], length: f64, from: usize) {
+    for j in 0..a[0].len() {
+        let s = 2.0
+            * v.iter()
+                .zip(&a[from..])
+                .map(|(v, row)| v * row[j])
+                .sum::<f64>()
+            / length;
+        for (v, row) in v.iter().zip(a[from..].iter_mut()) {
+            row[j] -= s * v;
+        }
+    }
+}
+
+// Reflects the columns of a from a column on, as in a H.
+fn reflect_columns(a: &mut Matrix, v: &[f64], length: f64, from: usize) {
+    for row in a.iter_mut() {
+        let s = 2.0 * v.iter().zip(&row[from..]).map(|(v, x)| v * x).sum::<f64>() / length;
+        for (x, v) in row[from..].iter_mut().zip(v) {
+            *x -= s * v;
+        }
+    }
+}
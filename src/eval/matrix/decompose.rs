@@ -0,0 +1,266 @@
+use super::{
+    Matrix, SWEEPS, eliminate, householder, identity, leading, reflect_columns, reflect_rows,
+    rotate, scale, symmetric, transpose,
+};
+
+// P A = L U with partial pivoting, where L has ones on its diagonal and P reorders the rows.
+pub fn lu(a: &Matrix) -> (Matrix, Matrix, Matrix) {
+    let n = a.len();
+    let mut u = a.clone();
+    let mut l = vec![vec![0.0; n]; n];
+    let mut p = identity(n);
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&i, &j| u[i][k].abs().total_cmp(&u[j][k].abs()))
+            .unwrap();
+        u.swap(pivot, k);
+        l.swap(pivot, k);
+        p.swap(pivot, k);
+        if u[k][k] == 0.0 {
+            continue;
+        }
+        for i in k + 1..n {
+            let factor = u[i][k] / u[k][k];
+            l[i][k] = factor;
+            eliminate(&mut u, k, i, factor, k);
+            u[i][k] = 0.0;
+        }
+    }
+    for (i, row) in l.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+    (l, u, p)
+}
+
+// A = Q R with Householder reflections, where Q is orthogonal and R is upper triangular with a
+// diagonal that isn't negative.
+pub fn qr(a: &Matrix) -> (Matrix, Matrix) {
+    let (rows, cols) = (a.len(), a[0].len());
+    let mut r = a.clone();
+    let mut q = identity(rows);
+    for k in 0..cols.min(rows - 1) {
+        let x: Vec<f64> = r[k..].iter().map(|row| row[k]).collect();
+        let Some((v, length)) = householder(&x) else {
+            continue;
+        };
+        reflect_rows(&mut r, &v, length, k);
+        reflect_columns(&mut q, &v, length, k);
+        for row in r[k + 1..].iter_mut() {
+            row[k] = 0.0;
+        }
+    }
+
+    for i in 0..cols.min(rows) {
+        // Subtracting from zero keeps zeros from turning into -0.
+        if r[i][i] < 0.0 {
+            r[i].iter_mut().for_each(|x| *x = 0.0 - *x);
+            q.iter_mut().for_each(|row| row[i] = 0.0 - row[i]);
+        }
+    }
+    (q, r)
+}
+
+// A = L Lᵀ for a symmetric positive definite matrix, with L lower triangular.
+pub fn cholesky(a: &Matrix) -> Result<Matrix, String> {
+    let n = a.len();
+    let tolerance = scale(a) * n as f64 * f64::EPSILON;
+    if !symmetric(a) {
+        return Err("Not symmetric: 'cholesky' needs a symmetric matrix".into());
+    }
+
+    let mut l = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let s = a[i][j] - (0..j).map(|k| l[i][k] * l[j][k]).sum::<f64>();
+            if i != j {
+                l[i][j] = s / l[j][j];
+            } else if s > tolerance {
+                l[i][i] = s.sqrt();
+            } else {
+                return Err(
+                    "Not positive definite: 'cholesky' needs a positive definite matrix".into(),
+                );
+            }
+        }
+    }
+    Ok(l)
+}
+
+// A = U S Vᵀ by one-sided Jacobi rotations, which make the columns of A orthogonal. Returns the
+// thin decomposition, with the singular values largest first.
+pub fn svd(a: &Matrix) -> Result<(Matrix, Vec<f64>, Matrix), String> {
+    let (rows, cols) = (a.len(), a[0].len());
+    if rows < cols {
+        let (v, s, u) = svd(&transpose(a))?;
+        return Ok((u, s, v));
+    }
+
+    // Columns are kept as rows here, so that each rotation works on two rows.
+    let mut u = transpose(a);
+    let mut v = identity(cols);
+    let mut converged = false;
+    for _ in 0..SWEEPS {
+        converged = true;
+        for p in 0..cols {
+            for q in p + 1..cols {
+                let alpha: f64 = u[p].iter().map(|x| x * x).sum();
+                let beta: f64 = u[q].iter().map(|x| x * x).sum();
+                let gamma: f64 = u[p].iter().zip(&u[q]).map(|(x, y)| x * y).sum();
+                if gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt() {
+                    continue;
+                }
+                converged = false;
+
+                let zeta = (beta - alpha) / (2.0 * gamma);
+                let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                let c = 1.0 / (1.0 + t * t).sqrt();
+                let s = c * t;
+                rotate(&mut u, p, q, c, s);
+                rotate(&mut v, p, q, c, s);
+            }
+        }
+        if converged {
+            break;
+        }
+    }
+    if !converged {
+        return Err("No convergence: 'svd' could not finish its decomposition".into());
+    }
+
+    let mut order: Vec<(f64, usize)> = u
+        .iter()
+        .enumerate()
+        .map(|(j, column)| (column.iter().map(|x| x * x).sum::<f64>().sqrt(), j))
+        .collect();
+    order.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let tolerance = scale(a) * rows as f64 * f64::EPSILON;
+    let mut singular = Vec::new();
+    let mut left: Matrix = Vec::new();
+    let mut right: Matrix = Vec::new();
+    for (sigma, j) in order {
+        let mut v = v[j].clone();
+        let mut u = u[j].clone();
+        if sigma <= tolerance {
+            singular.push(0.0);
+            u = complete(&left, rows);
+        } else {
+            singular.push(sigma);
+            u.iter_mut().for_each(|x| *x /= sigma);
+        }
+
+        // The largest part of each right vector is positive, so that the result is repeatable.
+        if leading(&v) < 0.0 {
+            v.iter_mut().for_each(|x| *x = -*x);
+            u.iter_mut().for_each(|x| *x = -*x);
+        }
+        left.push(u);
+        right.push(v);
+    }
+    Ok((transpose(&left), singular, transpose(&right)))
+}
+
+// A unit vector orthogonal to the given ones, found by projecting them out of an axis.
+fn complete(vectors: &Matrix, n: usize) -> Vec<f64> {
+    for axis in 0..n {
+        let mut x = vec![0.0; n];
+        x[axis] = 1.0;
+        for v in vectors {
+            let dot: f64 = x.iter().zip(v).map(|(x, v)| x * v).sum();
+            x.iter_mut().zip(v).for_each(|(x, v)| *x -= dot * v);
+        }
+        let norm = x.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm > 0.5 {
+            return x.into_iter().map(|x| x / norm).collect();
+        }
+    }
+    vec![0.0; n]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Matrix, cholesky, lu, qr, svd, transpose};
+
+    fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+        a.iter()
+            .map(|row| {
+                (0..b[0].len())
+                    .map(|j| row.iter().zip(b).map(|(x, b)| x * b[j]).sum())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn assert_close(a: &Matrix, b: &Matrix) {
+        assert_eq!((a.len(), a[0].len()), (b.len(), b[0].len()));
+        for (x, y) in a.iter().flatten().zip(b.iter().flatten()) {
+            assert!((x - y).abs() < 1e-12, "{:?} is not {:?}", a, b);
+        }
+    }
+
+    fn sample() -> Matrix {
+        vec![
+            vec![2.0, -1.0, 3.0],
+            vec![4.0, 1.0, -2.0],
+            vec![-6.0, 5.0, 1.0],
+        ]
+    }
+
+    #[test]
+    fn lu_reconstructs_permuted_matrix() {
+        let a = sample();
+        let (l, u, p) = lu(&a);
+        for (i, row) in l.iter().enumerate() {
+            assert_eq!(row[i], 1.0);
+            assert!(row[i + 1..].iter().all(|&x| x == 0.0));
+        }
+        for (i, row) in u.iter().enumerate() {
+            assert!(row[..i].iter().all(|&x| x == 0.0));
+        }
+        assert_close(&multiply(&p, &a), &multiply(&l, &u));
+    }
+
+    #[test]
+    fn qr_reconstructs_matrix() {
+        let a = vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]];
+        let (q, r) = qr(&a);
+        assert_close(&multiply(&transpose(&q), &q), &super::identity(3));
+        for (i, row) in r.iter().enumerate() {
+            assert!(row[..i.min(2)].iter().all(|&x| x == 0.0));
+        }
+        assert_close(&multiply(&q, &r), &a);
+    }
+
+    #[test]
+    fn cholesky_reconstructs_matrix() {
+        let a = vec![
+            vec![4.0, 12.0, -16.0],
+            vec![12.0, 37.0, -43.0],
+            vec![-16.0, -43.0, 98.0],
+        ];
+        let l = cholesky(&a).unwrap();
+        assert_eq!(
+            l,
+            vec![
+                vec![2.0, 0.0, 0.0],
+                vec![6.0, 1.0, 0.0],
+                vec![-8.0, 5.0, 3.0],
+            ]
+        );
+        assert_close(&multiply(&l, &transpose(&l)), &a);
+        assert!(cholesky(&sample()).is_err());
+    }
+
+    #[test]
+    fn svd_reconstructs_matrix() {
+        for a in [sample(), vec![vec![3.0, 2.0, 2.0], vec![2.0, 3.0, -2.0]]] {
+            let (u, s, v) = svd(&a).unwrap();
+            assert!(s.windows(2).all(|pair| pair[0] >= pair[1]));
+            let us: Matrix = u
+                .iter()
+                .map(|row| row.iter().zip(&s).map(|(x, s)| x * s).collect())
+                .collect();
+            assert_close(&multiply(&us, &transpose(&v)), &a);
+        }
+    }
+}
@@ -0,0 +1,489 @@
+use std::ops::{Add, Div, Mul, Sub};
+
+use super::{
+    Matrix, SWEEPS, householder, identity, leading, reflect_columns, reflect_rows, rotate, scale,
+    symmetric,
+};
+
+// Just enough complex arithmetic for the eigenvalues and eigenvectors of real matrices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Complex::new(re, 0.0)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+
+    fn div(self, other: Complex) -> Complex {
+        let d = other.re * other.re + other.im * other.im;
+        Complex::new(
+            (self.re * other.re + self.im * other.im) / d,
+            (self.im * other.re - self.re * other.im) / d,
+        )
+    }
+}
+
+// Eigenvalues, largest first, with unit eigenvectors whose largest part is positive. Symmetric
+// matrices have real ones that Jacobi rotations find directly, while for others the values come
+// from the QR algorithm and each vector from inverse iteration.
+pub fn eig(a: &Matrix) -> Result<(Vec<Complex>, Vec<Vec<Complex>>), String> {
+    let n = a.len();
+    if symmetric(a) {
+        let (values, vectors) = jacobi(a)?;
+        let real = |v: Vec<f64>| v.into_iter().map(Complex::from).collect();
+        return Ok(tidy(
+            a,
+            real(values),
+            vectors.into_iter().map(real).collect(),
+        ));
+    }
+
+    let mut values = eigenvalues(hessenberg(a))?;
+    values.sort_by(|x, y| y.re.total_cmp(&x.re).then(y.im.total_cmp(&x.im)));
+
+    let mut vectors: Vec<Vec<Complex>> = Vec::new();
+    while vectors.len() < n {
+        let value = values[vectors.len()];
+        let repeats = values[vectors.len()..]
+            .iter()
+            .take_while(|&&x| (x - value).abs() <= 1e-8 * value.abs().max(1.0))
+            .count();
+        if repeats == 1 {
+            vectors.push(eigenvector(a, value));
+            continue;
+        }
+
+        // A repeated eigenvalue may have several independent eigenvectors, which inverse
+        // iteration can't tell apart, or fewer than it repeats, in which case the last is
+        // repeated too.
+        let mut basis = null_space(a, value);
+        if basis.is_empty() {
+            basis.push(eigenvector(a, value));
+        }
+        for k in 0..repeats {
+            vectors.push(basis[k.min(basis.len() - 1)].clone());
+        }
+    }
+    Ok(tidy(a, values, vectors))
+}
+
+// Clears parts too small to tell from rounding errors, like the imaginary parts of real
+// eigenvectors.
+fn tidy(
+    a: &Matrix,
+    values: Vec<Complex>,
+    vectors: Vec<Vec<Complex>>,
+) -> (Vec<Complex>, Vec<Vec<Complex>>) {
+    let tolerance = 64.0 * a.len() as f64 * f64::EPSILON;
+    let clear = |z: Complex, size: f64| {
+        let part = |x: f64| if x.abs() <= tolerance * size { 0.0 } else { x };
+        Complex::new(part(z.re), part(z.im))
+    };
+    let size = scale(a);
+    (
+        values.into_iter().map(|z| clear(z, size)).collect(),
+        vectors
+            .into_iter()
+            .map(|v| v.into_iter().map(|z| clear(z, 1.0)).collect())
+            .collect(),
+    )
+}
+
+fn norm(v: &[Complex]) -> f64 {
+    v.iter()
+        .map(|x| x.re * x.re + x.im * x.im)
+        .sum::<f64>()
+        .sqrt()
+}
+
+// Scales a vector to unit length, turned so that its largest part is real and positive.
+fn normalize(v: Vec<Complex>) -> Vec<Complex> {
+    let max = v.iter().fold(0.0, |max: f64, x| max.max(x.abs()));
+    let Some(&top) = v.iter().find(|x| x.abs() >= max * (1.0 - 1e-9)) else {
+        return v;
+    };
+    let turn = top / Complex::from(top.abs() / norm(&v));
+    v.into_iter().map(|x| x / turn).collect()
+}
+
+// Cyclic Jacobi rotations, each clearing one off-diagonal pair, until the matrix is diagonal.
+fn jacobi(a: &Matrix) -> Result<(Vec<f64>, Matrix), String> {
+    let n = a.len();
+    let size = a.iter().flatten().map(|x| x * x).sum::<f64>().sqrt();
+    let mut a = a.clone();
+    // The eigenvectors are kept as rows, so that each rotation works on two rows.
+    let mut v = identity(n);
+    let mut converged = false;
+    for _ in 0..SWEEPS {
+        let off: f64 = (0..n)
+            .flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+            .map(|(p, q)| a[p][q] * a[p][q])
+            .sum();
+        if off.sqrt() <= f64::EPSILON * size {
+            converged = true;
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = c * t;
+                for row in a.iter_mut() {
+                    (row[p], row[q]) = (c * row[p] - s * row[q], s * row[p] + c * row[q]);
+                }
+                rotate(&mut a, p, q, c, s);
+                rotate(&mut v, p, q, c, s);
+            }
+        }
+    }
+    if !converged {
+        return Err("No convergence: 'eig' could not find the eigenvalues".into());
+    }
+
+    let mut pairs: Vec<(f64, Vec<f64>)> = v
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut v)| {
+            if leading(&v) < 0.0 {
+                v.iter_mut().for_each(|x| *x = -*x);
+            }
+            (a[i][i], v)
+        })
+        .collect();
+    pairs.sort_by(|x, y| y.0.total_cmp(&x.0));
+    Ok(pairs.into_iter().unzip())
+}
+
+// Reduces a matrix to upper Hessenberg form, zero below its first subdiagonal, with the same
+// eigenvalues.
+fn hessenberg(a: &Matrix) -> Matrix {
+    let n = a.len();
+    let mut h = a.clone();
+    for k in 0..n.saturating_sub(2) {
+        let x: Vec<f64> = h[k + 1..].iter().map(|row| row[k]).collect();
+        let Some((v, length)) = householder(&x) else {
+            continue;
+        };
+        reflect_rows(&mut h, &v, length, k + 1);
+        reflect_columns(&mut h, &v, length, k + 1);
+        for row in h[k + 2..].iter_mut() {
+            row[k] = 0.0;
+        }
+    }
+    h
+}
+
+// The QR algorithm with Francis's double shift, splitting off eigenvalues from the bottom of
+// the Hessenberg matrix one or two at a time as its subdiagonal vanishes.
+pub fn eigenvalues(mut h: Matrix) -> Result<Vec<Complex>, String> {
+    let tolerance = scale(&h) * f64::EPSILON;
+    let limit = 30 * h.len();
+    let mut values = Vec::new();
+    let mut n = h.len();
+    let mut iterations = 0;
+    while n > 0 {
+        let l = (1..n)
+            .rev()
+            .find(|&i| {
+                let near = h[i - 1][i - 1].abs() + h[i][i].abs();
+                h[i][i - 1].abs() <= f64::EPSILON * near || h[i][i - 1].abs() <= tolerance
+            })
+            .unwrap_or(0);
+        if l > 0 {
+            h[l][l - 1] = 0.0;
+        }
+
+        match n - l {
+            1 => {
+                values.push(Complex::from(h[n - 1][n - 1]));
+                n -= 1;
+                iterations = 0;
+            }
+            2 => {
+                let (a, b) = (h[n - 2][n - 2], h[n - 2][n - 1]);
+                let (c, d) = (h[n - 1][n - 2], h[n - 1][n - 1]);
+                values.extend(pair(a, b, c, d));
+                n -= 2;
+                iterations = 0;
+            }
+            _ if iterations == limit => {
+                return Err("No convergence: 'eig' could not find the eigenvalues".into());
+            }
+            _ => {
+                iterations += 1;
+                francis(&mut h, l, n, iterations);
+            }
+        }
+    }
+    Ok(values)
+}
+
+// The eigenvalues of the 2×2 matrix [[a, b], [c, d]].
+fn pair(a: f64, b: f64, c: f64, d: f64) -> [Complex; 2] {
+    let mean = (a + d) / 2.0;
+    let half = (a - d) / 2.0;
+    let discriminant = half * half + b * c;
+    if discriminant < 0.0 {
+        let im = (-discriminant).sqrt();
+        return [Complex::new(mean, im), Complex::new(mean, -im)];
+    }
+
+    // The root further from zero comes first, and the product gives the other without losing
+    // digits to cancellation.
+    let first = mean + discriminant.sqrt().copysign(mean);
+    let second = match first {
+        0.0 => mean - discriminant.sqrt(),
+        first => (a * d - b * c) / first,
+    };
+    [Complex::from(first), Complex::from(second)]
+}
+
+// One double-shift step on rows and columns l to n, chasing the bulge it makes down the matrix.
+fn francis(h: &mut Matrix, l: usize, n: usize, iterations: usize) {
+    let (a, b) = (h[n - 2][n - 2], h[n - 2][n - 1]);
+    let (c, d) = (h[n - 1][n - 2], h[n - 1][n - 1]);
+    let (mut s, mut t) = (a + d, a * d - b * c);
+    // Now and then an unusual shift breaks the cycles that the usual one can fall into.
+    if iterations.is_multiple_of(10) {
+        let w = h[n - 1][n - 2].abs() + h[n - 2][n - 3].abs();
+        (s, t) = (1.5 * w, w * w);
+    }
+
+    let mut x = h[l][l] * h[l][l] + h[l][l + 1] * h[l + 1][l] - s * h[l][l] + t;
+    let mut y = h[l + 1][l] * (h[l][l] + h[l + 1][l + 1] - s);
+    let mut z = h[l + 1][l] * h[l + 2][l + 1];
+    for k in l..n - 1 {
+        let size = (n - k).min(3);
+        if let Some((v, length)) = householder(&[x, y, z][..size]) {
+            reflect_rows(h, &v, length, k);
+            reflect_columns(h, &v, length, k);
+        }
+        if k + 2 < n {
+            x = h[k + 1][k];
+            y = h[k + 2][k];
+            z = if k + 3 < n { h[k + 3][k] } else { 0.0 };
+        }
+    }
+}
+
+// Inverse iteration, which solves (a - μI) x = b repeatedly so that x turns towards the
+// eigenvectors for eigenvalues near μ. Taking μ just off the eigenvalue keeps the system
+// solvable, and amplifies every direction of a repeated eigenvalue's eigenvectors alike.
+fn eigenvector(a: &Matrix, value: Complex) -> Vec<Complex> {
+    let n = a.len();
+    let tiny = Complex::from(scale(a) * n as f64 * f64::EPSILON);
+    let value = value + tiny;
+    let mut m = shifted(a, value);
+
+    // LU factors with partial pivoting, kept in place.
+    let mut order: Vec<usize> = (0..n).collect();
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&i, &j| m[i][k].abs().total_cmp(&m[j][k].abs()))
+            .unwrap();
+        m.swap(pivot, k);
+        order.swap(pivot, k);
+        if m[k][k].abs() == 0.0 {
+            m[k][k] = tiny;
+        }
+        let row = m[k].clone();
+        for below in m[k + 1..].iter_mut() {
+            let factor = below[k] / row[k];
+            below[k] = factor;
+            for (x, p) in below[k + 1..].iter_mut().zip(&row[k + 1..]) {
+                *x = *x - factor * *p;
+            }
+        }
+    }
+
+    let mut x = vec![Complex::from(1.0); n];
+    for _ in 0..3 {
+        let mut y: Vec<Complex> = order.iter().map(|&i| x[i]).collect();
+        for i in 0..n {
+            for j in 0..i {
+                y[i] = y[i] - m[i][j] * y[j];
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                y[i] = y[i] - m[i][j] * y[j];
+            }
+            y[i] = y[i] / m[i][i];
+        }
+        x = normalize(y);
+    }
+    x
+}
+
+// a - λI, in complex numbers.
+fn shifted(a: &Matrix, value: Complex) -> Vec<Vec<Complex>> {
+    a.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(j, &x)| match i == j {
+                    true => Complex::from(x) - value,
+                    false => Complex::from(x),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Orthonormal vectors spanning the solutions of (a - λI) x = 0, from its reduced row echelon
+// form. The tolerance allows for an eigenvalue that's only accurate to about half its digits.
+fn null_space(a: &Matrix, value: Complex) -> Vec<Vec<Complex>> {
+    let n = a.len();
+    let tolerance = scale(a) * n as f64 * 1e-8;
+    let mut m = shifted(a, value);
+    let mut pivots = Vec::new();
+    for k in 0..n {
+        let r = pivots.len();
+        if r == n {
+            break;
+        }
+        let pivot = (r..n)
+            .max_by(|&i, &j| m[i][k].abs().total_cmp(&m[j][k].abs()))
+            .unwrap();
+        if m[pivot][k].abs() <= tolerance {
+            continue;
+        }
+        m.swap(pivot, r);
+        let p = m[r][k];
+        m[r].iter_mut().for_each(|x| *x = *x / p);
+        let row = m[r].clone();
+        for (_, other) in m.iter_mut().enumerate().filter(|(i, _)| *i != r) {
+            let factor = other[k];
+            for (x, p) in other.iter_mut().zip(&row) {
+                *x = *x - factor * *p;
+            }
+        }
+        pivots.push(k);
+    }
+
+    let mut basis: Vec<Vec<Complex>> = Vec::new();
+    for free in (0..n).filter(|k| !pivots.contains(k)) {
+        let mut x = vec![Complex::from(0.0); n];
+        x[free] = Complex::from(1.0);
+        for (row, &k) in pivots.iter().enumerate() {
+            x[k] = Complex::from(0.0) - m[row][free];
+        }
+        for u in basis.iter() {
+            let dot = u
+                .iter()
+                .zip(&x)
+                .fold(Complex::from(0.0), |sum, (u, x)| sum + u.conj() * *x);
+            x = x.iter().zip(u).map(|(x, u)| *x - dot * *u).collect();
+        }
+        basis.push(normalize(x));
+    }
+    basis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Complex, eig};
+
+    fn assert_values(values: &[Complex], expected: &[Complex]) {
+        assert_eq!(values.len(), expected.len());
+        for (x, y) in values.iter().zip(expected) {
+            assert!(
+                (*x - *y).abs() < 1e-12,
+                "{:?} is not {:?}",
+                values,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn rotation_has_imaginary_eigenvalues() {
+        let a = vec![vec![0.0, -1.0], vec![1.0, 0.0]];
+        let (values, vectors) = eig(&a).unwrap();
+        assert_values(&values, &[Complex::new(0.0, 1.0), Complex::new(0.0, -1.0)]);
+        for (value, vector) in values.iter().zip(&vectors) {
+            for (row, x) in a.iter().zip(vector) {
+                let product = row
+                    .iter()
+                    .zip(vector)
+                    .fold(Complex::from(0.0), |sum, (a, v)| {
+                        sum + Complex::from(*a) * *v
+                    });
+                assert!((product - *value * *x).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric_matrix_has_known_eigenvalues() {
+        let a = vec![
+            vec![2.0, -1.0, 0.0],
+            vec![-1.0, 2.0, -1.0],
+            vec![0.0, -1.0, 2.0],
+        ];
+        let (values, _) = eig(&a).unwrap();
+        let root = 2f64.sqrt();
+        assert_values(
+            &values,
+            &[
+                Complex::from(2.0 + root),
+                Complex::from(2.0),
+                Complex::from(2.0 - root),
+            ],
+        );
+    }
+}
@@ -2,17 +2,24 @@ use crate::eval::types::{Expr, Token};
 use std::{iter::Peekable, slice::Iter};
 
 pub mod delimeter;
+pub mod infix;
 pub mod num;
 pub mod prefix;
-pub mod infix;
 
 pub fn parse(tokens: Vec<Token>) -> Result<Expr, String> {
     primary(&mut tokens.iter().peekable(), 0)
 }
 
 pub fn primary(tokens: &mut Peekable<Iter<Token>>, precedence: u8) -> Result<Expr, String> {
-    let mut left = prefix::parse(tokens)?;
+    let left = prefix::parse(tokens)?;
+    climb(tokens, left, precedence)
+}
 
+pub fn climb(
+    tokens: &mut Peekable<Iter<Token>>,
+    mut left: Expr,
+    precedence: u8,
+) -> Result<Expr, String> {
     while let Some(&token) = tokens.peek() {
         if token.precedence() < precedence {
             break;
@@ -1,12 +1,15 @@
 use std::{iter::Peekable, slice::Iter};
 
-use crate::eval::{types::{Expr, Token}, parser};
+use crate::eval::{
+    parser,
+    types::{Expr, Token},
+};
 
 pub fn paren(tokens: &mut Peekable<Iter<Token>>) -> Result<Expr, String> {
     let mut inside = Vec::new();
     let mut depth = 1;
 
-    while let Some(token) = tokens.next() {
+    for token in tokens.by_ref() {
         match token {
             Token::LeftParen => {
                 depth += 1;
@@ -32,3 +35,73 @@ pub fn paren(tokens: &mut Peekable<Iter<Token>>) -> Result<Expr, String> {
 
     parser::parse(inside)
 }
+
+// Arguments of a function call, split on the commas that aren't nested in parentheses.
+pub fn args(tokens: &mut Peekable<Iter<Token>>) -> Result<Vec<Expr>, String> {
+    items(tokens, &Token::RightParen)
+}
+
+// Items of a list literal like [1, 2, 3], after its opening '['.
+pub fn list(tokens: &mut Peekable<Iter<Token>>) -> Result<Vec<Expr>, String> {
+    items(tokens, &Token::RightBracket)
+}
+
+fn items(tokens: &mut Peekable<Iter<Token>>, close: &Token) -> Result<Vec<Expr>, String> {
+    groups(tokens, close)?
+        .into_iter()
+        .map(parser::parse)
+        .collect()
+}
+
+// The tokens of each item up to the closing token, for callers that look at them before parsing.
+pub fn groups(
+    tokens: &mut Peekable<Iter<Token>>,
+    close: &Token,
+) -> Result<Vec<Vec<Token>>, String> {
+    let mut args = Vec::new();
+    let mut inside = Vec::new();
+    let mut depth = 1;
+
+    for token in tokens.by_ref() {
+        match token {
+            Token::LeftParen | Token::LeftBracket => depth += 1,
+            Token::RightParen | Token::RightBracket => {
+                depth -= 1;
+                if depth == 0 && token != close {
+                    return Err(format!(
+                        "Unexpected '{}': Expected '{}' to close the opening one first",
+                        token, close
+                    ));
+                }
+                if depth == 0 {
+                    break;
+                }
+            }
+            Token::Comma if depth == 1 => {
+                args.push(std::mem::take(&mut inside));
+                continue;
+            }
+            _ => {}
+        }
+        inside.push(token.to_owned());
+    }
+
+    if depth != 0 && close == &Token::RightBracket {
+        return Err(format!(
+            "Unclosed bracket: {} unmatched '[' or '('. Expected {} closing ']' or ')' before end of expression.",
+            depth, depth
+        ));
+    }
+    if depth != 0 {
+        return Err(format!(
+            "Unclosed parenthesis: {} unmatched '('. Expected {} closing ')' before end of expression.",
+            depth, depth
+        ));
+    }
+
+    if !inside.is_empty() || !args.is_empty() {
+        args.push(inside);
+    }
+
+    Ok(args)
+}
@@ -1,7 +1,10 @@
 use std::{iter::Peekable, slice::Iter};
 
+use num_traits::ToPrimitive;
+
 use crate::eval::{
-    parser::{delimeter, primary},
+    parser::{climb, delimeter, num, prefix::ident, primary},
+    radix,
     types::{Expr, Operator, Token},
 };
 
@@ -40,6 +43,15 @@ pub fn parse(tokens: &mut Peekable<Iter<Token>>, left: Expr) -> Result<Expr, Str
                 Box::new(right),
             ))
         }
+        Token::DoubleSlash | Token::Mod | Token::Rem => {
+            let op = match token {
+                Token::DoubleSlash => Operator::FloorDivision,
+                Token::Mod => Operator::Modulo,
+                _ => Operator::Remainder,
+            };
+            let right = primary(tokens, token.precedence() + 1)?;
+            Ok(Expr::Bin(Box::new(left), op, Box::new(right)))
+        }
         Token::Carrot => {
             let right = primary(tokens, token.precedence())?;
             Ok(Expr::Bin(
@@ -62,48 +74,161 @@ pub fn parse(tokens: &mut Peekable<Iter<Token>>, left: Expr) -> Result<Expr, Str
 
             Ok(Expr::Unary(Operator::Factorial(amount), Box::new(left)))
         }
+        // Whether a name is called or multiplied depends on what it holds, which is only known
+        // once it's evaluated.
+        Token::LeftParen
+            if matches!(
+                left,
+                Expr::Var(..)
+                    | Expr::Param(_)
+                    | Expr::Lambda(..)
+                    | Expr::Call(..)
+                    | Expr::Func(..)
+                    | Expr::Index(..)
+                    | Expr::Value(_)
+            ) =>
+        {
+            Ok(Expr::Call(Box::new(left), delimeter::args(tokens)?))
+        }
         Token::LeftParen => Ok(Expr::Bin(
             Box::new(left),
             Operator::Multiplication,
             Box::new(delimeter::paren(tokens)?),
         )),
-        Token::Percent => match left {
-            Expr::Num(n) => Ok(Expr::Bin(
-                Box::new(Expr::Num(1.0)),
-                Operator::Percent,
-                Box::new(Expr::Num(n)),
-            )),
-            Expr::Bin(l, op, r) => Ok(Expr::Bin(
-                l.clone(),
-                op,
-                Box::new(Expr::Bin(l, Operator::Percent, r)),
-            )),
-            Expr::Unary(op, r) => Ok(Expr::Unary(
-                op,
-                Box::new(Expr::Bin(r.clone(), Operator::Percent, r)),
-            )),
-            Expr::Func(id, args) => Ok(Expr::Bin(
-                Box::new(Expr::Func(id.clone(), args.clone())),
-                Operator::Percent,
-                Box::new(Expr::Func(id, args)),
-            )),
-            Expr::Var(_, value) => Ok(Expr::Bin(
-                Box::new(Expr::Num(1.0)),
-                Operator::Percent,
-                value,
-            )),
+        Token::Identifier(id) => {
+            let ident = ident::parse(tokens, id)?;
+            let right = climb(tokens, ident, token.precedence() + 1)?;
+            Ok(Expr::Bin(
+                Box::new(left),
+                Operator::Multiplication,
+                Box::new(right),
+            ))
+        }
+        Token::Num(_) | Token::Int(_) if num::is_quantity(&left) => {
+            let right = match token {
+                Token::Num(n) => num::parse(tokens, Expr::Num(*n))?,
+                Token::Int(n) => num::parse(tokens, Expr::Int(n.clone()))?,
+                _ => unreachable!(),
+            };
+            Ok(Expr::Bin(
+                Box::new(left),
+                Operator::Addition,
+                Box::new(right),
+            ))
+        }
+        // '%' is always a postfix percentage, so a number straight after it is most likely an
+        // attempt at a remainder.
+        Token::Percent if matches!(tokens.peek(), Some(Token::Num(_) | Token::Int(_))) => {
+            Err(format!(
+                "Unexpected '{}' after '%': '%' makes a percentage, use 'mod' or 'rem' for remainders",
+                tokens.peek().unwrap()
+            ))
+        }
+        Token::Percent => match tokens.peek() {
+            Some(Token::Of) => {
+                tokens.next();
+                let right = primary(tokens, Token::Star.precedence() + 1)?;
+                Ok(Expr::Bin(
+                    Box::new(left),
+                    Operator::Percent,
+                    Box::new(right),
+                ))
+            }
+            _ => Ok(Expr::Unary(Operator::Percent, Box::new(left))),
         },
-        Token::Equal => {
-            let right = primary(tokens, 0)?;
+        Token::Arrow => {
+            let right = primary(tokens, token.precedence() + 1)?;
+            Ok(Expr::Bin(Box::new(left), Operator::Change, Box::new(right)))
+        }
+        Token::As => {
+            match (tokens.next(), tokens.next()) {
+                (Some(Token::Percent), Some(Token::Of)) => {}
+                _ => return Err("Unexpected 'as': Expected 'as % of' followed by a value".into()),
+            }
+
+            let right = primary(tokens, token.precedence() + 1)?;
             Ok(Expr::Bin(
                 Box::new(left),
-                Operator::Equal,
+                Operator::Proportion,
                 Box::new(right),
             ))
         }
+        Token::To => {
+            if let Some(Token::Identifier(id)) = tokens.peek()
+                && let Some(base) = radix(tokens, id)?
+            {
+                return Ok(Expr::Unary(Operator::Radix(base), Box::new(left)));
+            }
+
+            let right = primary(tokens, token.precedence() + 1)?;
+            Ok(Expr::Bin(
+                Box::new(left),
+                Operator::Conversion,
+                Box::new(right),
+            ))
+        }
+        Token::Bar | Token::Xor | Token::Ampersand | Token::ShiftLeft | Token::ShiftRight => {
+            let op = match token {
+                Token::Bar => Operator::BitOr,
+                Token::Xor => Operator::BitXor,
+                Token::Ampersand => Operator::BitAnd,
+                Token::ShiftLeft => Operator::ShiftLeft,
+                _ => Operator::ShiftRight,
+            };
+            let right = primary(tokens, token.precedence() + 1)?;
+            Ok(Expr::Bin(Box::new(left), op, Box::new(right)))
+        }
+        // Ranges are inclusive, as in 1..10, with an optional step as in 0..1 step 0.1.
+        Token::DotDot => {
+            let end = primary(tokens, token.precedence() + 1)?;
+            let step = match tokens.peek() {
+                Some(Token::Step) => {
+                    tokens.next();
+                    Some(Box::new(primary(tokens, token.precedence() + 1)?))
+                }
+                _ => None,
+            };
+            Ok(Expr::Range(Box::new(left), Box::new(end), step))
+        }
+        Token::LeftBracket => {
+            let mut index = delimeter::list(tokens)?;
+            if index.len() != 1 {
+                return Err(format!(
+                    "Invalid index for '{}': Expected a single index between '[' and ']'",
+                    left
+                ));
+            }
+            Ok(Expr::Index(Box::new(left), Box::new(index.remove(0))))
+        }
+        Token::Equal => {
+            let right = primary(tokens, 0)?;
+            Ok(Expr::Bin(Box::new(left), Operator::Equal, Box::new(right)))
+        }
         token => Err(format!(
             "Unknown operator '{}': Expected a valid known operator",
             token
         )),
     }
 }
+
+// Output bases for conversions like `to hex` or `to base 36`.
+fn radix(tokens: &mut Peekable<Iter<Token>>, id: &str) -> Result<Option<u32>, String> {
+    if let Some(base) = radix::named(id) {
+        tokens.next();
+        return Ok(Some(base));
+    }
+
+    if id != "base" {
+        return Ok(None);
+    }
+
+    tokens.next();
+    match tokens.next() {
+        Some(Token::Int(n)) if let Some(n @ 2..=36) = n.to_u32() => Ok(Some(n)),
+        Some(token) => Err(format!(
+            "Invalid base '{}': Expected a whole number between 2 and 36 after 'base'",
+            token
+        )),
+        None => Err("Unexpected end of expression: Expected a number after 'base'".into()),
+    }
+}
@@ -1,28 +1,50 @@
 use std::{iter::Peekable, slice::Iter};
 
 use crate::eval::{
-    parser::{delimeter, prefix::ident},
+    parser::{self, delimeter, prefix::ident},
     types::{Expr, Operator, Token},
 };
 
-pub fn parse(tokens: &mut Peekable<Iter<Token>>, num: f64) -> Result<Expr, String> {
+pub fn parse(tokens: &mut Peekable<Iter<Token>>, num: Expr) -> Result<Expr, String> {
     match tokens.peek() {
         Some(Token::LeftParen) => {
             tokens.next();
             Ok(Expr::Bin(
-                Box::new(Expr::Num(num)),
+                Box::new(num),
                 Operator::Multiplication,
                 Box::new(delimeter::paren(tokens)?),
             ))
         }
         Some(Token::Identifier(id)) => {
             tokens.next();
+            let ident = ident::parse(tokens, id)?;
             Ok(Expr::Bin(
-                Box::new(Expr::Num(num)),
+                Box::new(num),
                 Operator::Multiplication,
-                Box::new(ident::parse(tokens, id)?),
+                Box::new(parser::climb(tokens, ident, Token::Carrot.precedence())?),
             ))
         }
-        _ => Ok(Expr::Num(num)),
+        _ => Ok(num),
+    }
+}
+
+// A time of day like 14:30 is read as the duration since midnight.
+pub fn time(secs: f64) -> Expr {
+    Expr::Bin(
+        Box::new(Expr::Num(secs)),
+        Operator::Multiplication,
+        Box::new(Expr::Unit("s".to_string())),
+    )
+}
+
+// Whether an expression ends in a number with a unit, so that a following number continues it
+// as in 1h 30min or 5 ft 3 inch.
+pub fn is_quantity(expr: &Expr) -> bool {
+    match expr {
+        Expr::Bin(left, Operator::Multiplication, right) => {
+            matches!(**left, Expr::Num(_) | Expr::Int(_)) && matches!(**right, Expr::Unit(_))
+        }
+        Expr::Bin(_, Operator::Addition, right) => is_quantity(right),
+        _ => false,
     }
 }
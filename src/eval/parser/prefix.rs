@@ -1,7 +1,8 @@
 use std::{iter::Peekable, slice::Iter};
 
 use crate::eval::{
-    parser::{delimeter, num},
+    environment,
+    parser::{climb, delimeter, num, primary},
     types::{Expr, Operator, Token},
 };
 
@@ -9,17 +10,40 @@ pub mod ident;
 
 pub fn parse(tokens: &mut Peekable<Iter<Token>>) -> Result<Expr, String> {
     match tokens.next() {
-            Some(Token::Num(n)) => num::parse(tokens, *n),
+            Some(Token::Num(n)) => num::parse(tokens, Expr::Num(*n)),
+            Some(Token::Int(n)) => num::parse(tokens, Expr::Int(n.clone())),
+            Some(Token::Date(d)) => Ok(Expr::Date(d.clone())),
+            Some(Token::Dice(d)) => Ok(Expr::Dice(d.clone())),
+            Some(Token::Time(t)) => Ok(num::time(*t)),
+            Some(Token::Plus) => parse(tokens),
+            Some(Token::LeftParen) if let Some(params) = parameters(tokens) => {
+                tokens.nth(params.len() * 2);
+                lambda(tokens, params)
+            }
             Some(Token::LeftParen) => Ok(delimeter::paren(tokens)?),
+            Some(Token::LeftBracket) => Ok(Expr::List(delimeter::list(tokens)?)),
             Some(Token::Minus) => match tokens.next() {
-                Some(Token::Num(n)) => Ok(Expr::Unary(Operator::Subtraction, Box::new(num::parse(tokens, *n)?))),
+                Some(Token::Num(n)) => num::parse(tokens, Expr::Num(-n)),
+                Some(Token::Int(n)) => num::parse(tokens, Expr::Int(-n)),
+                Some(Token::Time(t)) => Ok(num::time(-t)),
                 Some(Token::LeftParen) => Ok(Expr::Unary(Operator::Subtraction, Box::new(delimeter::paren(tokens)?))),
-                Some(Token::Identifier(id)) => Ok(Expr::Unary(Operator::Subtraction, Box::new(ident::parse(tokens, id)?))),
+                Some(Token::LeftBracket) => Ok(Expr::Unary(Operator::Subtraction, Box::new(Expr::List(delimeter::list(tokens)?)))),
+                Some(Token::Identifier(id)) => {
+                    let ident = ident::parse(tokens, id)?;
+                    Ok(Expr::Unary(Operator::Subtraction, Box::new(climb(tokens, ident, Token::LeftParen.precedence())?)))
+                }
                 Some(token) => Err(format!("Unexpected token '{}' after unary '-': Expected a number, an opening parenthesis '(', or a valid unary expression.", token)),
                 None => Err("Unexpected end of expression: Expected a number, '(', or unary operator before end.".into()),
             },
+            // A name before '->' is always a parameter, even one like t that's also a unit, so a
+            // percent change from a variable is written (old) -> new.
+            Some(Token::Identifier(id)) if tokens.peek() == Some(&&Token::Arrow) => {
+                tokens.next();
+                lambda(tokens, vec![id.to_string()])
+            }
             Some(Token::Identifier(id)) => ident::parse(tokens, id),
             Some(Token::Bar) => ident::func::absolute(tokens),
+            Some(Token::Tilde) => Ok(Expr::Unary(Operator::BitNot, Box::new(primary(tokens, Token::Carrot.precedence())?))),
             Some(token) => Err(format!(
                 "Unexpected token '{}' encountered: Expected a number, an opening parenthesis '(', or a unary operator.",
                 token
@@ -27,3 +51,47 @@ pub fn parse(tokens: &mut Peekable<Iter<Token>>) -> Result<Expr, String> {
             None => Err("Unexpected end of expression: Expected a number, '(', or unary operator before end.".into()),
         }
 }
+
+// A lambda's body, in which its parameters stand for the values it will be called with.
+fn lambda(tokens: &mut Peekable<Iter<Token>>, params: Vec<String>) -> Result<Expr, String> {
+    let body = scoped(&params, || primary(tokens, 0))?;
+    Ok(Expr::Lambda(params, Box::new(body)))
+}
+
+// Runs a parse in which the given names are parameters rather than variables or units.
+pub fn scoped<T>(params: &[String], parse: impl FnOnce() -> T) -> T {
+    let depth = {
+        let mut scope = environment::fetch_parameters().lock().unwrap();
+        scope.extend(params.iter().cloned());
+        scope.len() - params.len()
+    };
+    let result = parse();
+    environment::fetch_parameters()
+        .lock()
+        .unwrap()
+        .truncate(depth);
+    result
+}
+
+// Parameters of a lambda like (x, y) -> x + y, after its opening '('. A single name in
+// parentheses is left as an expression, so that (old) -> new is still a percent change.
+fn parameters(tokens: &Peekable<Iter<Token>>) -> Option<Vec<String>> {
+    let mut ahead = tokens.clone();
+    let mut params = Vec::new();
+    loop {
+        match ahead.next()? {
+            Token::Identifier(id) => params.push(id.to_string()),
+            _ => return None,
+        }
+        match ahead.next()? {
+            Token::Comma => continue,
+            Token::RightParen => break,
+            _ => return None,
+        }
+    }
+
+    match ahead.next() {
+        Some(Token::Arrow) if params.len() > 1 => Some(params),
+        _ => None,
+    }
+}
@@ -2,19 +2,150 @@ use std::{iter::Peekable, slice::Iter};
 
 use crate::eval::{
     types::{Expr, Token},
+    unit as units,
 };
 
 pub mod func;
+pub mod unit;
 pub mod var;
 
 pub fn parse(tokens: &mut Peekable<Iter<Token>>, id: &str) -> Result<Expr, String> {
     match id {
-        "sqrt" | "ln" | "root" | "log" | "cbrt" | "sin" | "cos" | "tan" | "cot" | "sec" | "csc"
-        | "asin" | "acos" | "atan" | "acot" | "asec" | "acsc" | "sinh" | "cosh" | "tanh"
-        | "coth" | "sech" | "csch" | "asinh" | "acosh" | "atanh" | "acoth" | "asech" | "acsch" => {
+        // Names like min that are also units are only functions when they're called.
+        _ if builtin(id)
+            && (tokens.peek() == Some(&&Token::LeftParen) || units::find(id).is_none()) =>
+        {
             func::parse(tokens, id)
         }
+        "d" if func::leibniz(tokens).is_some() => func::derivative(tokens),
+
+        "unit" => unit::parse(tokens),
 
         _ => var::parse(tokens, id),
     }
 }
+
+pub fn builtin(id: &str) -> bool {
+    matches!(
+        id,
+        "sqrt"
+            | "ln"
+            | "root"
+            | "log"
+            | "cbrt"
+            | "sin"
+            | "cos"
+            | "tan"
+            | "cot"
+            | "sec"
+            | "csc"
+            | "asin"
+            | "acos"
+            | "atan"
+            | "acot"
+            | "asec"
+            | "acsc"
+            | "sinh"
+            | "cosh"
+            | "tanh"
+            | "coth"
+            | "sech"
+            | "csch"
+            | "asinh"
+            | "acosh"
+            | "atanh"
+            | "acoth"
+            | "asech"
+            | "acsch"
+            | "mean"
+            | "median"
+            | "mode"
+            | "var"
+            | "varp"
+            | "stdev"
+            | "stdevp"
+            | "quantile"
+            | "min"
+            | "max"
+            | "sum"
+            | "prod"
+            | "range"
+            | "geomean"
+            | "harmean"
+            | "len"
+            | "map"
+            | "filter"
+            | "reduce"
+            | "sort"
+            | "zip"
+            | "compose"
+            | "identity"
+            | "transpose"
+            | "det"
+            | "inv"
+            | "rank"
+            | "trace"
+            | "dot"
+            | "cross"
+            | "norm"
+            | "linsolve"
+            | "solve"
+            | "lu"
+            | "qr"
+            | "cholesky"
+            | "svd"
+            | "eig"
+            | "roots"
+            | "polyval"
+            | "polymul"
+            | "polydiv"
+            | "polyder"
+            | "deriv"
+            | "integrate"
+            | "floor"
+            | "ceil"
+            | "round"
+            | "roundto"
+            | "trunc"
+            | "frac"
+            | "sign"
+            | "clamp"
+            | "nCr"
+            | "nPr"
+            | "gcd"
+            | "lcm"
+            | "isprime"
+            | "nextprime"
+            | "factor"
+            | "totient"
+            | "divisors"
+            | "fib"
+            | "modpow"
+            | "gamma"
+            | "lgamma"
+            | "beta"
+            | "digamma"
+            | "erf"
+            | "erfc"
+            | "normpdf"
+            | "normcdf"
+            | "norminv"
+            | "binompdf"
+            | "binomcdf"
+            | "poissonpdf"
+            | "poissoncdf"
+            | "tcdf"
+            | "chi2cdf"
+            | "expcdf"
+            | "unifcdf"
+            | "bits"
+            | "rand"
+            | "randn"
+            | "randint"
+            | "now"
+            | "weekday"
+            | "isoweek"
+            | "unix"
+            | "fromunix"
+    )
+}
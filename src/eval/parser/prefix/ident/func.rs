@@ -1,45 +1,44 @@
-use std::{iter::Peekable, slice::Iter};
+use std::{iter::Peekable, ops::RangeInclusive, slice::Iter};
 
 use crate::eval::{
-    parser::{self, delimeter},
-    types::{Expr, Operator, Token},
+    parser::{
+        self, delimeter,
+        prefix::{
+            ident::{builtin, var},
+            scoped,
+        },
+    },
+    types::{Expr, Function, Operator, Token, Value},
+    unit as units,
 };
 
 pub fn parse(tokens: &mut Peekable<Iter<Token>>, id: &str) -> Result<Expr, String> {
     match tokens.next() {
         Some(Token::LeftParen) => match id {
-            "root" => {
-                let mut radicand = Vec::new();
-                while let Some(next_token) = tokens.next() {
-                    if next_token == &Token::Comma {
-                        break;
-                    }
-
-                    radicand.push(next_token.to_owned());
-                }
-                Ok(Expr::Func(
-                    id.to_string(),
-                    vec![
-                        Box::new(parser::parse(radicand)?),
-                        Box::new(delimeter::paren(tokens)?),
-                    ],
-                ))
-            }
+            "now" | "rand" | "randn" => match tokens.next() {
+                Some(Token::RightParen) => Ok(Expr::Func(id.to_string(), vec![])),
+                _ => Err(format!("Unexpected argument: '{}' takes no arguments", id)),
+            },
+            "solve" => solve(tokens),
+            "roots" => roots(tokens),
+            "deriv" | "integrate" => calculus(tokens, id),
             "log" => Ok(Expr::Func(
                 id.to_string(),
-                vec![
-                    Box::new(Expr::Num(10.0)),
-                    Box::new(delimeter::paren(tokens)?),
-                ],
-            )),
-            _ => Ok(Expr::Func(
-                id.to_string(),
-                vec![Box::new(delimeter::paren(tokens)?)],
+                vec![Expr::Num(10.0), delimeter::paren(tokens)?],
             )),
+            _ if arity(id) != (1..=1) => {
+                let args = delimeter::groups(tokens, &Token::RightParen)?
+                    .into_iter()
+                    .map(|group| argument(id, group))
+                    .collect::<Result<Vec<_>, _>>()?;
+                check(id, args.len())?;
+                Ok(Expr::Func(id.to_string(), args))
+            }
+            _ => Ok(Expr::Func(id.to_string(), vec![delimeter::paren(tokens)?])),
         },
         Some(Token::Underscore) => {
             let mut base = Vec::new();
-            while let Some(next_token) = tokens.next() {
+            for next_token in tokens.by_ref() {
                 if next_token == &Token::LeftParen {
                     break;
                 }
@@ -49,50 +48,334 @@ pub fn parse(tokens: &mut Peekable<Iter<Token>>, id: &str) -> Result<Expr, Strin
 
             Ok(Expr::Func(
                 id.to_string(),
-                vec![
-                    Box::new(parser::parse(base)?),
-                    Box::new(delimeter::paren(tokens)?),
-                ],
+                vec![parser::parse(base)?, delimeter::paren(tokens)?],
             ))
         }
-        None => Err(
-            "Unexpected end of expression: Expected a number, '(', or unary operator before end"
-                .into(),
-        ),
-        token => Err(format!(
+        Some(token) => Err(format!(
             "Unexpected '{}': Expected parenthesis after '{}'",
-            token.unwrap(),
+            token, id
+        )),
+        None => Err(format!(
+            "Unexpected end of expression: Expected parenthesis after '{}'",
             id
         )),
     }
 }
 
-pub fn absolute(tokens: &mut Peekable<Iter<Token>>) -> Result<Expr, String> {
-    let mut expr = Vec::new();
-    while let Some(token) = tokens.next() {
-        if token == &Token::Bar {
-            break;
+// An argument of a function that takes functions, like map, where a built-in function's name on
+// its own is the function itself, as in map(sqrt, xs).
+fn argument(id: &str, group: Vec<Token>) -> Result<Expr, String> {
+    let takes = matches!(
+        id,
+        "map"
+            | "filter"
+            | "reduce"
+            | "sort"
+            | "compose"
+            | "solve"
+            | "roots"
+            | "deriv"
+            | "integrate"
+    );
+    match &group[..] {
+        [Token::Identifier(name)] if takes && builtin(name) => Ok(Expr::Value(Value::Function(
+            Function::Builtin(name.to_string()),
+        ))),
+        _ => parser::parse(group),
+    }
+}
+
+// A system of equations like solve(2x + y = 3, x - y = 0), whose unknowns are the names that
+// aren't functions, variables or parameters, or one equation with its unknown named after it,
+// as in solve(cos(x) = x, x), to solve numerically from an optional guess or interval. Either
+// becomes a function of the unknowns giving each equation's left side minus its right side,
+// which the executor then solves like any other function passed to it.
+fn solve(tokens: &mut Peekable<Iter<Token>>) -> Result<Expr, String> {
+    let mut equations = delimeter::groups(tokens, &Token::RightParen)?;
+    check("solve", equations.len())?;
+
+    if let [_, name, ..] = &equations[..]
+        && let [Token::Identifier(id)] = &name[..]
+        && !builtin(id)
+    {
+        if equations.len() > 3 {
+            return Err(format!(
+                "Wrong number of arguments: 'solve' takes an equation, its unknown and a guess or interval but got {}",
+                equations.len()
+            ));
+        }
+        let unknown = vec![id.to_string()];
+        let side = scoped(&unknown, || side(equations.remove(0)))?;
+        let mut args = vec![Expr::Lambda(unknown, Box::new(side))];
+        for near in equations.into_iter().skip(1) {
+            args.push(parser::parse(near)?);
         }
+        return Ok(Expr::Func("solve".to_string(), args));
+    }
 
-        expr.push(token.to_owned());
+    let mut unknowns: Vec<String> = Vec::new();
+    for token in equations.iter().flatten() {
+        if let Token::Identifier(id) = token
+            && !builtin(id)
+            && !var::defined(id)
+            && units::find(id).is_none()
+            && !unknowns.contains(id)
+        {
+            unknowns.push(id.to_string());
+        }
+    }
+    // A function of one value, as in solve(f, 0.5), is solved for where it's zero.
+    let function = !equations[0].contains(&Token::Equal)
+        && (unknowns.is_empty() || equations[0].contains(&Token::Arrow));
+    if function && equations.len() <= 2 {
+        let args = equations
+            .into_iter()
+            .map(|group| argument("solve", group))
+            .collect::<Result<_, _>>()?;
+        return Ok(Expr::Func("solve".to_string(), args));
+    }
+    if unknowns.is_empty() {
+        return Err("Nothing to solve: Expected an unknown like 'x' in the equations".into());
+    }
+
+    let sides = scoped(&unknowns, || {
+        equations
+            .into_iter()
+            .map(side)
+            .collect::<Result<_, String>>()
+    })?;
+    Ok(Expr::Func(
+        "solve".to_string(),
+        vec![Expr::Lambda(unknowns, Box::new(Expr::List(sides)))],
+    ))
+}
+
+// The roots of a polynomial given by its coefficients, highest power first, or written out with
+// its unknown named after it, as in roots(x^2 - 3x + 2, x).
+fn roots(tokens: &mut Peekable<Iter<Token>>) -> Result<Expr, String> {
+    let mut groups = delimeter::groups(tokens, &Token::RightParen)?;
+    check("roots", groups.len())?;
+
+    if let [polynomial, name] = &groups[..]
+        && let [Token::Identifier(id)] = &name[..]
+        && !builtin(id)
+        && polynomial.contains(&Token::Identifier(id.to_string()))
+    {
+        let unknown = vec![id.to_string()];
+        let side = scoped(&unknown, || side(groups.remove(0)))?;
+        return Ok(Expr::Func(
+            "roots".to_string(),
+            vec![Expr::Lambda(unknown, Box::new(side))],
+        ));
+    }
+
+    let args = groups
+        .into_iter()
+        .map(|group| argument("roots", group))
+        .collect::<Result<_, _>>()?;
+    Ok(Expr::Func("roots".to_string(), args))
+}
+
+// A derivative or integral of a function, as in deriv(sin, 0), or of an expression with its
+// variable named after it, as in integrate(sin(x), x, 0, pi), which becomes a function of it.
+fn calculus(tokens: &mut Peekable<Iter<Token>>, id: &str) -> Result<Expr, String> {
+    let mut groups = delimeter::groups(tokens, &Token::RightParen)?;
+    let points = match id {
+        "deriv" => "a point",
+        _ => "two bounds",
+    };
+    let wrong = format!(
+        "Wrong number of arguments: '{}' takes a function and {}, or an expression, its variable and {}, but got {}",
+        id,
+        points,
+        points,
+        groups.len()
+    );
+
+    // A name that isn't defined can only be the variable, even with arguments missing.
+    let count = arity(id);
+    if let Some([Token::Identifier(variable)]) = groups.get(1).map(|group| &group[..])
+        && !builtin(variable)
+        && (groups.len() == count.end() + 1 || !var::defined(variable))
+    {
+        if groups.len() != count.end() + 1 {
+            return Err(wrong);
+        }
+        let variable = variable.to_string();
+        groups.remove(1);
+        return bound(id, &variable, groups);
+    }
+    if !count.contains(&groups.len()) {
+        return Err(wrong);
+    }
+
+    let args = groups
+        .into_iter()
+        .map(|group| argument(id, group))
+        .collect::<Result<_, _>>()?;
+    Ok(Expr::Func(id.to_string(), args))
+}
+
+// The variable of a derivative written as d/dx, after the 'd'.
+pub fn leibniz(tokens: &Peekable<Iter<Token>>) -> Option<String> {
+    let mut ahead = tokens.clone();
+    match (ahead.next(), ahead.next(), ahead.next()) {
+        (Some(Token::Slash), Some(Token::Identifier(name)), Some(Token::LeftParen)) => name
+            .strip_prefix('d')
+            .filter(|variable| !variable.is_empty())
+            .map(String::from),
+        _ => None,
+    }
+}
+
+// A derivative like d/dx(x^3, 2), taken in the variable named after the 'd' at a point.
+pub fn derivative(tokens: &mut Peekable<Iter<Token>>) -> Result<Expr, String> {
+    let variable = leibniz(tokens).unwrap();
+    // Past the '/dx('.
+    tokens.nth(2);
+    let groups = delimeter::groups(tokens, &Token::RightParen)?;
+    if groups.len() != 2 {
+        return Err(format!(
+            "Wrong number of arguments: 'd/d{}' takes an expression and a point but got {}",
+            variable,
+            groups.len()
+        ));
+    }
+    bound("deriv", &variable, groups)
+}
+
+// A function of the variable given by the first group, followed by the other groups.
+fn bound(id: &str, variable: &str, mut groups: Vec<Vec<Token>>) -> Result<Expr, String> {
+    let variable = vec![variable.to_string()];
+    let body = scoped(&variable, || parser::parse(groups.remove(0)))?;
+    let mut args = vec![Expr::Lambda(variable, Box::new(body))];
+    for group in groups {
+        args.push(parser::parse(group)?);
+    }
+    Ok(Expr::Func(id.to_string(), args))
+}
+
+// An equation's left side minus its right side, which is zero where it holds. The sides are
+// parsed apart so that a name before '=' isn't taken for an assignment, and an expression
+// without '=' is taken as equal to zero.
+fn side(mut equation: Vec<Token>) -> Result<Expr, String> {
+    let mut depth = 0;
+    let equal = equation.iter().position(|token| {
+        match token {
+            Token::LeftParen | Token::LeftBracket => depth += 1,
+            Token::RightParen | Token::RightBracket => depth -= 1,
+            _ => {}
+        }
+        depth == 0 && token == &Token::Equal
+    });
+    let Some(equal) = equal else {
+        return parser::parse(equation);
+    };
+
+    let right = equation.split_off(equal + 1);
+    equation.pop();
+    Ok(Expr::Bin(
+        Box::new(parser::parse(equation)?),
+        Operator::Subtraction,
+        Box::new(parser::parse(right)?),
+    ))
+}
+
+// How many arguments each function takes.
+pub fn arity(id: &str) -> RangeInclusive<usize> {
+    match id {
+        "now" | "rand" | "randn" => 0..=0,
+        "bits" | "round" | "log" | "sort" | "norm" => 1..=2,
+        "root" | "beta" | "roundto" | "nCr" | "nPr" | "randint" | "poissonpdf" | "poissoncdf"
+        | "tcdf" | "chi2cdf" | "expcdf" | "map" | "filter" | "zip" | "dot" | "cross"
+        | "linsolve" | "polyval" | "polymul" | "polydiv" | "deriv" => 2..=2,
+        "clamp" | "modpow" | "integrate" | "binompdf" | "binomcdf" | "unifcdf" => 3..=3,
+        "normpdf" | "normcdf" | "norminv" => 1..=3,
+        "reduce" => 2..=3,
+        "compose" => 2..=usize::MAX,
+        "mean" | "median" | "mode" | "var" | "varp" | "stdev" | "stdevp" | "quantile" | "min"
+        | "max" | "sum" | "prod" | "range" | "geomean" | "harmean" | "gcd" | "lcm" | "solve"
+        | "roots" => 1..=usize::MAX,
+        _ => 1..=1,
+    }
+}
+
+// Checks a call against how many arguments the function accepts.
+pub fn check(id: &str, got: usize) -> Result<(), String> {
+    if matches!(id, "normpdf" | "normcdf" | "norminv") && got == 2 {
+        return Err(format!(
+            "Wrong number of arguments: '{}' takes a value alone or with a mean and a standard deviation",
+            id
+        ));
+    }
+
+    let count = arity(id);
+    if !count.contains(&got) {
+        let expected = match (count.start(), count.end()) {
+            (start, end) if start == end => start.to_string(),
+            (start, &usize::MAX) => format!("at least {}", start),
+            (start, end) => format!("{} to {}", start, end),
+        };
+        return Err(format!(
+            "Wrong number of arguments: '{}' takes {} but got {}",
+            id, expected, got
+        ));
+    }
+
+    Ok(())
+}
+
+// The inside of |x| binds tighter than '|', so the next bar outside any parentheses closes it
+// and a bitwise or inside is written in parentheses, as in |(1 | 2) - 5|.
+pub fn absolute(tokens: &mut Peekable<Iter<Token>>) -> Result<Expr, String> {
+    let inside = parser::primary(tokens, Token::Xor.precedence())?;
+    match tokens.next() {
+        Some(Token::Bar) => {}
+        Some(token) => {
+            return Err(format!(
+                "Unexpected '{}': Expected '|' to close the absolute value",
+                token
+            ));
+        }
+        None => {
+            return Err(
+                "Unexpected end of expression: Expected '|' to close the absolute value".into(),
+            );
+        }
     }
 
     match tokens.peek() {
-        Some(Token::Num(n)) => {
+        Some(token @ (Token::Num(_) | Token::Int(_))) => {
+            let n = match token {
+                Token::Num(n) => Expr::Num(*n),
+                Token::Int(n) => Expr::Int(n.clone()),
+                _ => unreachable!(),
+            };
             tokens.next();
 
             Ok(Expr::Bin(
-                Box::new(Expr::Unary(
-                    Operator::Absolute,
-                    Box::new(parser::parse(expr)?),
-                )),
+                Box::new(Expr::Unary(Operator::Absolute, Box::new(inside))),
                 Operator::Multiplication,
-                Box::new(Expr::Num(*n)),
+                Box::new(n),
             ))
         }
-        _ => Ok(Expr::Unary(
-            Operator::Absolute,
-            Box::new(parser::parse(expr)?),
-        )),
+        _ => Ok(Expr::Unary(Operator::Absolute, Box::new(inside))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval::session::eval;
+
+    #[test]
+    fn absolute_value_beside_bitwise_or() {
+        assert_eq!(eval("|(1 | 2) - 5|"), "2");
+        assert_eq!(eval("||-3| - 5|"), "2");
+        assert_eq!(eval("|2 - 5| | 8"), "11");
+        assert_eq!(eval("|-3| 2"), "6");
+        assert_eq!(
+            eval("|3 - 4"),
+            "Unexpected end of expression: Expected '|' to close the absolute value"
+        );
     }
 }
@@ -0,0 +1,89 @@
+use std::{iter::Peekable, slice::Iter};
+
+use crate::eval::{
+    environment, executor,
+    parser::{self, prefix::ident::var},
+    types::{Expr, Token},
+    unit::{self, Dimension, Prefix, Unit},
+};
+
+pub fn parse(tokens: &mut Peekable<Iter<Token>>) -> Result<Expr, String> {
+    let name = match tokens.next() {
+        Some(Token::Identifier(name)) => name,
+        Some(token) => {
+            return Err(format!(
+                "Unexpected '{}': Expected a name for the unit after 'unit'",
+                token
+            ));
+        }
+        None => return Err("Unexpected end of expression: Expected a name after 'unit'".into()),
+    };
+
+    if unit::find(name).is_some() {
+        return Err(format!(
+            "Duplicate unit '{}': A unit with this name is already defined",
+            name
+        ));
+    }
+    // A variable would be found before the unit, which could then never be used.
+    if var::defined(name) {
+        return Err(format!(
+            "Duplicate unit '{}': A variable with this name is already defined",
+            name
+        ));
+    }
+
+    // Without a definition the unit becomes a new base dimension of its own.
+    let unit = match tokens.next() {
+        Some(Token::Equal) => {
+            let quantity = executor::calculate(&parser::primary(tokens, 0)?)?.quantity()?;
+            Unit {
+                name: name.to_string(),
+                factor: quantity.value,
+                offset: 0.0,
+                dim: quantity.dim,
+                prefix: Prefix::Metric,
+            }
+        }
+        None => Unit {
+            name: name.to_string(),
+            factor: 1.0,
+            offset: 0.0,
+            dim: Dimension::from(&[(name.as_str(), 1)][..]),
+            prefix: Prefix::Metric,
+        },
+        Some(token) => {
+            return Err(format!(
+                "Unexpected '{}': Expected '=' followed by the unit's definition",
+                token
+            ));
+        }
+    };
+
+    environment::fetch_units().lock().unwrap().push(unit);
+    Ok(Expr::Num(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval::session::eval;
+
+    #[test]
+    fn unit_names_must_be_free() {
+        assert_eq!(
+            eval("unit pi = 2 m"),
+            "Duplicate unit 'pi': A variable with this name is already defined"
+        );
+        assert_eq!(eval("wage = 3"), "1");
+        assert_eq!(
+            eval("unit wage"),
+            "Duplicate unit 'wage': A variable with this name is already defined"
+        );
+        assert_eq!(
+            eval("unit bar = 2 m"),
+            "Duplicate unit 'bar': A unit with this name is already defined"
+        );
+        assert_eq!(eval("unit smoot = 1.7018 m"), "1");
+        assert_eq!(eval("2 smoot to m"), "3.4036 m");
+    }
+}
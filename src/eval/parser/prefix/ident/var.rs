@@ -1,32 +1,86 @@
 use std::{iter::Peekable, slice::Iter};
 
 use crate::eval::{
-    environment, parser,
+    environment, executor, parser,
     types::{Expr, Token},
+    unit,
 };
 
+// Whether a name is a lambda parameter or a variable, rather than free to stand for something new.
+pub fn defined(id: &str) -> bool {
+    environment::fetch_parameters()
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|param| param == id)
+        || environment::fetch_variables()
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|expr| matches!(expr, Expr::Var(ident, _) if ident == id))
+}
+
 pub fn parse(tokens: &mut Peekable<Iter<Token>>, id: &str) -> Result<Expr, String> {
+    // Inside a lambda its parameters hide variables and units of the same name.
+    if environment::fetch_parameters()
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|param| param == id)
+    {
+        return Ok(Expr::Param(id.to_string()));
+    }
+
     {
         let variables = environment::fetch_variables().lock().unwrap();
         for expr in variables.iter() {
-            if let Expr::Var(ident, value) = expr {
-                if ident.as_str() == id {
-                    return Ok(Expr::Var(ident.to_string(), value.to_owned()));
-                }
+            if let Expr::Var(ident, value) = expr
+                && ident.as_str() == id
+            {
+                return Ok(Expr::Var(ident.to_string(), value.to_owned()));
             }
         }
 
         if tokens.peek() != Some(&&Token::Equal) {
+            if unit::find(id).is_some() {
+                return Ok(Expr::Unit(id.to_string()));
+            }
+
             return Err(format!(
-                "Unknown variable '{}': Expected a valid variable that has been defined",
+                "Unknown variable '{}': Expected a valid variable or unit that has been defined",
                 id
             ));
         }
     }
 
     tokens.next();
-    let expr = parser::primary(tokens, 0)?;
+    let mut expr = parser::primary(tokens, 0)?;
+    // Dice and random numbers are drawn once, so that the variable keeps the same value, while a
+    // function that draws them still does so on each call.
+    if expr.random() && !matches!(expr, Expr::Lambda(..)) {
+        expr = Expr::Value(executor::calculate(&expr)?);
+    }
     let mut variables = environment::fetch_variables().lock().unwrap();
     variables.push(Expr::Var(id.to_string(), Box::new(expr)));
-    return Ok(Expr::Num(1.0));
+    Ok(Expr::Num(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval::session::eval;
+
+    #[test]
+    fn random_values_are_drawn_once() {
+        eval("rolled = 10d6");
+        eval("drawn = rand()");
+        let (rolled, drawn) = (eval("rolled"), eval("drawn"));
+        for _ in 0..5 {
+            assert_eq!(eval("rolled"), rolled);
+            assert_eq!(eval("drawn"), drawn);
+        }
+
+        eval("roller = t -> t + 100d6");
+        let rolls: Vec<String> = (0..5).map(|_| eval("roller(0)")).collect();
+        assert!(rolls.iter().any(|roll| roll != &rolls[0]));
+    }
 }
@@ -0,0 +1,219 @@
+use crate::eval::matrix::eigen::{self, Complex};
+
+// The most Newton steps taken to refine a root, which stops sooner once they stop helping.
+const ITERATIONS: usize = 50;
+
+// Every root of the polynomial with these coefficients, highest power first, sorted like
+// eigenvalues with the largest first. They're the eigenvalues of its companion matrix, each
+// refined afterwards by Newton's method on the polynomial itself.
+pub fn roots(coefficients: &[f64]) -> Result<Vec<Complex>, String> {
+    let Some(start) = coefficients.iter().position(|&c| c != 0.0) else {
+        return Err("Every value is a root: The polynomial is zero everywhere".into());
+    };
+    let p = &coefficients[start..];
+
+    // Roots at zero are split off exactly, as the power of x that divides the polynomial.
+    let zeros = p.iter().rev().take_while(|&&c| c == 0.0).count();
+    let p = &p[..p.len() - zeros];
+    let n = p.len() - 1;
+
+    let mut roots = Vec::new();
+    if n > 0 {
+        let mut companion = vec![vec![0.0; n]; n];
+        for (j, c) in p[1..].iter().enumerate() {
+            companion[0][j] = -c / p[0];
+        }
+        for (i, row) in companion.iter_mut().enumerate().skip(1) {
+            row[i - 1] = 1.0;
+        }
+        let found = eigen::eigenvalues(companion)
+            .map_err(|_| "No convergence: 'roots' could not find the roots".to_string())?;
+        roots = polish(p, found);
+    }
+    roots.extend(std::iter::repeat_n(Complex::from(0.0), zeros));
+    roots.sort_by(|x, y| y.re.total_cmp(&x.re).then(y.im.total_cmp(&x.im)));
+    Ok(roots)
+}
+
+// The polynomial's value at z, and its derivative's, by Horner's rule.
+fn horner(p: &[f64], z: Complex) -> (Complex, Complex) {
+    let mut value = Complex::from(0.0);
+    let mut slope = Complex::from(0.0);
+    for &c in p {
+        slope = slope * z + value;
+        value = value * z + Complex::from(c);
+    }
+    (value, slope)
+}
+
+fn derivative(p: &[f64]) -> Vec<f64> {
+    let n = p.len() - 1;
+    p[..n]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c * (n - i) as f64)
+        .collect()
+}
+
+// Whether the polynomial is zero at z as far as evaluating it there can tell, allowing for the
+// rounding errors in z as well as those that add up while evaluating.
+fn vanishes(p: &[f64], z: Complex) -> bool {
+    let size = z.abs();
+    let noise =
+        64.0 * p.len() as f64 * f64::EPSILON * p.iter().fold(0.0, |sum, c| sum * size + c.abs());
+    horner(p, z).0.abs() <= noise
+}
+
+// Newton steps for as long as they bring the polynomial closer to zero.
+fn refine(p: &[f64], mut z: Complex) -> Complex {
+    let mut y = horner(p, z).0.abs();
+    for _ in 0..ITERATIONS {
+        let (value, slope) = horner(p, z);
+        if y == 0.0 || slope.abs() == 0.0 {
+            break;
+        }
+        let next = z - value / slope;
+        let next_y = horner(p, next).0.abs();
+        if next_y.is_nan() || next_y >= y {
+            break;
+        }
+        (z, y) = (next, next_y);
+    }
+    z
+}
+
+// A root repeated k times comes out of the eigenvalues as k roots scattered around it, by
+// about the k-th root of the rounding errors. When the polynomial and its first k - 1
+// derivatives all vanish at the middle of such a cluster, it's taken for the repeated root, and
+// otherwise each root is refined on its own. Either way it's then rounded, judged by the
+// polynomial it's a simple root of, since one that only touches zero can't tell roots apart.
+fn polish(p: &[f64], mut roots: Vec<Complex>) -> Vec<Complex> {
+    let mut done = vec![false; roots.len()];
+    for i in 0..roots.len() {
+        if done[i] {
+            continue;
+        }
+        let cluster: Vec<usize> = (i..roots.len())
+            .filter(|&j| !done[j] && (roots[j] - roots[i]).abs() <= 1e-2 * roots[i].abs())
+            .collect();
+        let sum = cluster
+            .iter()
+            .fold(Complex::from(0.0), |sum, &j| sum + roots[j]);
+        let middle = sum / Complex::from(cluster.len() as f64);
+
+        let mut q = p.to_vec();
+        let mut repeated = cluster.len() > 1;
+        for _ in 1..cluster.len() {
+            repeated &= vanishes(&q, middle);
+            q = derivative(&q);
+        }
+        if repeated && vanishes(&q, middle) {
+            // The repeated root is a simple root of the last of those derivatives.
+            let root = round(&q, refine(&q, middle));
+            for &j in &cluster {
+                roots[j] = root;
+                done[j] = true;
+            }
+        } else {
+            roots[i] = round(p, refine(p, roots[i]));
+        }
+    }
+    roots
+}
+
+// The roundest nearby number that's at least as good a root, or the root with only its tiny
+// parts cleared, as for the real part of a root that should be imaginary.
+fn round(p: &[f64], z: Complex) -> Complex {
+    let size = z.abs();
+    let y = horner(p, z).0.abs();
+    for rounded in [true, false] {
+        let round = |x: f64| match x.abs() <= 1e-11 * size {
+            true => 0.0,
+            false if rounded => {
+                let scale = 10_f64.powi(11 - x.abs().log10().floor() as i32);
+                (x * scale).round() / scale
+            }
+            false => x,
+        };
+        let candidate = Complex::new(round(z.re), round(z.im));
+        if candidate.re.is_finite()
+            && candidate.im.is_finite()
+            && match rounded {
+                true => horner(p, candidate).0.abs() <= y,
+                false => vanishes(p, candidate),
+            }
+        {
+            return candidate;
+        }
+    }
+    z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Complex, polish, roots};
+
+    fn real(values: &[f64]) -> Vec<Complex> {
+        values.iter().map(|&x| Complex::from(x)).collect()
+    }
+
+    #[test]
+    fn distinct_real_roots() {
+        assert_eq!(roots(&[1.0, -3.0, 2.0]).unwrap(), real(&[2.0, 1.0]));
+        assert_eq!(
+            roots(&[1.0, -6.0, 11.0, -6.0]).unwrap(),
+            real(&[3.0, 2.0, 1.0])
+        );
+        assert_eq!(roots(&[2.0, -1.0]).unwrap(), real(&[0.5]));
+    }
+
+    #[test]
+    fn repeated_roots() {
+        assert_eq!(
+            roots(&[1.0, -3.0, 3.0, -1.0]).unwrap(),
+            real(&[1.0, 1.0, 1.0])
+        );
+        // (x - 2)^2 (x + 1)
+        assert_eq!(
+            roots(&[1.0, -3.0, 0.0, 4.0]).unwrap(),
+            real(&[2.0, 2.0, -1.0])
+        );
+    }
+
+    #[test]
+    fn complex_roots() {
+        assert_eq!(
+            roots(&[1.0, 0.0, 1.0]).unwrap(),
+            [Complex::new(0.0, 1.0), Complex::new(0.0, -1.0)]
+        );
+        // (x - 1)(x^2 - 2x + 5), whose other roots are 1 ± 2i.
+        assert_eq!(
+            roots(&[1.0, -3.0, 7.0, -5.0]).unwrap(),
+            [
+                Complex::new(1.0, 2.0),
+                Complex::new(1.0, 0.0),
+                Complex::new(1.0, -2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_roots_and_leading_zeros() {
+        assert_eq!(
+            roots(&[0.0, 1.0, -2.0, 0.0, 0.0]).unwrap(),
+            real(&[2.0, 0.0, 0.0])
+        );
+        assert_eq!(roots(&[5.0]).unwrap(), []);
+        assert!(roots(&[0.0, 0.0]).is_err());
+    }
+
+    #[test]
+    fn polish_joins_scattered_repeated_root() {
+        // The eigenvalues of (x - 1)^2 come out around 1 by about the square root of the
+        // rounding errors.
+        let scattered = real(&[1.0 + 1e-8, 1.0 - 1e-8]);
+        assert_eq!(polish(&[1.0, -2.0, 1.0], scattered), real(&[1.0, 1.0]));
+        let nearby = real(&[2.0 + 1e-9, 1.0 - 1e-9]);
+        assert_eq!(polish(&[1.0, -3.0, 2.0], nearby), real(&[2.0, 1.0]));
+    }
+}
@@ -0,0 +1,124 @@
+use num_bigint::BigInt;
+
+// Output bases that can be named in conversions like `255 to hex`.
+pub fn named(name: &str) -> Option<u32> {
+    match name {
+        "hex" => Some(16),
+        "dec" => Some(10),
+        "oct" => Some(8),
+        "bin" => Some(2),
+        _ => None,
+    }
+}
+
+// Largest number of fractional digits shown, enough to cover the 52 bits of an f64 mantissa in
+// binary and fewer in larger bases.
+fn max_fraction_digits(base: u32) -> usize {
+    (52.0 / (base as f64).log2()).ceil() as usize
+}
+
+pub fn prefix(base: u32) -> String {
+    match base {
+        16 => "0x".to_string(),
+        8 => "0o".to_string(),
+        2 => "0b".to_string(),
+        _ => format!("{}#", base),
+    }
+}
+
+fn check(base: u32) -> Result<(), String> {
+    match (2..=36).contains(&base) {
+        true => Ok(()),
+        false => Err(format!(
+            "Invalid base '{}': Expected a base between 2 and 36",
+            base
+        )),
+    }
+}
+
+// Whole numbers are read exactly rather than through an f64.
+pub fn parse_int(digits: &str, base: u32) -> Result<BigInt, String> {
+    check(base)?;
+    if let Some(c) = digits.chars().find(|c| c.to_digit(base).is_none()) {
+        return Err(format!(
+            "Invalid digit '{}': Not a digit in base {}",
+            c, base
+        ));
+    }
+
+    BigInt::parse_bytes(digits.as_bytes(), base).ok_or(format!(
+        "Missing digits: Expected digits in base {} after the prefix",
+        base
+    ))
+}
+
+pub fn parse(digits: &str, base: u32) -> Result<f64, String> {
+    check(base)?;
+
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(format!(
+            "Missing digits: Expected digits in base {} after the prefix",
+            base
+        ));
+    }
+
+    let digit = |c: char| {
+        c.to_digit(base).map(f64::from).ok_or(format!(
+            "Invalid digit '{}': Not a digit in base {}",
+            c, base
+        ))
+    };
+
+    let mut value = 0.0;
+    for c in whole.chars() {
+        value = value * base as f64 + digit(c)?;
+    }
+
+    let mut scale = 1.0 / base as f64;
+    for c in fraction.chars() {
+        value += digit(c)? * scale;
+        scale /= base as f64;
+    }
+
+    Ok(value)
+}
+
+pub fn format(value: f64, base: u32) -> String {
+    if base == 10 || !value.is_finite() {
+        return value.to_string();
+    }
+
+    let prefix = prefix(base);
+    let sign = if value < 0.0 { "-" } else { "" };
+    let value = value.abs();
+
+    let mut whole = value.trunc();
+    let mut digits = Vec::new();
+    while whole >= 1.0 {
+        let digit = (whole % base as f64) as u32;
+        digits.push(std::char::from_digit(digit, base).unwrap());
+        whole = (whole / base as f64).trunc();
+    }
+    if digits.is_empty() {
+        digits.push('0');
+    }
+    digits.reverse();
+
+    let mut s: String = digits.into_iter().collect();
+    let mut fraction = value.fract();
+    if fraction > 0.0 {
+        s.push('.');
+        for _ in 0..max_fraction_digits(base) {
+            fraction *= base as f64;
+            let digit = fraction.trunc() as u32;
+            s.push(std::char::from_digit(digit, base).unwrap());
+            fraction = fraction.fract();
+            if fraction == 0.0 {
+                break;
+            }
+        }
+    }
+
+    format!("{}{}{}", sign, prefix, s)
+}
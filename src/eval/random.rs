@@ -0,0 +1,215 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::eval::environment;
+
+// xoshiro256**, a small generator that's fast and passes the usual statistical tests, see
+// https://prng.di.unimi.it/. It's not meant for anything cryptographic.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    // Spreads a seed over the whole state with splitmix64, so that nearby seeds give unrelated
+    // sequences and the state is never all zeros.
+    pub fn seeded(seed: u64) -> Rng {
+        let mut x = seed;
+        let mut state = [0; 4];
+        for word in state.iter_mut() {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            *word = z ^ (z >> 31);
+        }
+        Rng { state }
+    }
+
+    // Seeded from the clock when no seed has been set with `:seed`.
+    pub fn unseeded() -> Rng {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or(0);
+        Rng::seeded(nanos)
+    }
+
+    pub fn next(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    // Uniform in [0, 1), using the top 53 bits so every value is a multiple of 2^-53.
+    pub fn float(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform in [0, n), rejecting the few values that would make the low results more likely.
+    pub fn below(&mut self, n: u64) -> u64 {
+        let limit = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next();
+            if x < limit {
+                return x % n;
+            }
+        }
+    }
+
+    // A standard normal value, using the polar form of the Box-Muller transform.
+    pub fn normal(&mut self) -> f64 {
+        loop {
+            let u = 2.0 * self.float() - 1.0;
+            let v = 2.0 * self.float() - 1.0;
+            let s = u * u + v * v;
+            if s > 0.0 && s < 1.0 {
+                return u * (-2.0 * s.ln() / s).sqrt();
+            }
+        }
+    }
+}
+
+pub fn seed(seed: Option<u64>) {
+    *environment::fetch_random().lock().unwrap() = match seed {
+        Some(seed) => Rng::seeded(seed),
+        None => Rng::unseeded(),
+    };
+}
+
+pub fn float() -> f64 {
+    environment::fetch_random().lock().unwrap().float()
+}
+
+pub fn normal() -> f64 {
+    environment::fetch_random().lock().unwrap().normal()
+}
+
+// A whole number from low to high, including both.
+pub fn integer(low: &BigInt, high: &BigInt) -> Result<BigInt, String> {
+    if low > high {
+        return Err(format!(
+            "Invalid range for 'randint': Expected '{}' to be at most '{}'",
+            low, high
+        ));
+    }
+
+    let span = high - low;
+    let Some(span) = span.to_u64().filter(|span| *span < u64::MAX) else {
+        return Err(format!(
+            "Invalid range for 'randint': Expected at most {} values",
+            u64::MAX
+        ));
+    };
+    let mut rng = environment::fetch_random().lock().unwrap();
+    Ok(low + rng.below(span + 1))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Keep {
+    Highest(u32),
+    Lowest(u32),
+}
+
+// Tabletop dice like 3d6 or 4d6kh3, rolled again each time they're evaluated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dice {
+    pub count: u32,
+    pub sides: u32,
+    pub keep: Option<Keep>,
+}
+
+impl Dice {
+    pub fn roll(&self) -> BigInt {
+        self.roll_with(&mut environment::fetch_random().lock().unwrap())
+    }
+
+    fn roll_with(&self, rng: &mut Rng) -> BigInt {
+        let mut rolls: Vec<u64> = (0..self.count)
+            .map(|_| rng.below(self.sides as u64) + 1)
+            .collect();
+
+        rolls.sort_unstable();
+        let kept = match self.keep {
+            Some(Keep::Highest(n)) => &rolls[rolls.len() - n as usize..],
+            Some(Keep::Lowest(n)) => &rolls[..n as usize],
+            None => &rolls[..],
+        };
+        kept.iter().fold(BigInt::zero(), |sum, roll| sum + roll)
+    }
+}
+
+impl std::fmt::Display for Dice {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        match self.keep {
+            Some(Keep::Highest(n)) => write!(f, "kh{}", n),
+            Some(Keep::Lowest(n)) => write!(f, "kl{}", n),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+
+    use super::{Dice, Keep, Rng};
+
+    #[test]
+    fn fixed_seed_gives_fixed_sequence() {
+        let mut rng = Rng::seeded(42);
+        let sequence: Vec<u64> = (0..4).map(|_| rng.next()).collect();
+        assert_eq!(
+            sequence,
+            [
+                0x1578_0b2e_0c2e_c716,
+                0x6104_d986_6d11_3a7e,
+                0xae17_5332_39e4_99a1,
+                0xecb8_ad47_03b3_60a1,
+            ]
+        );
+    }
+
+    #[test]
+    fn same_seed_repeats() {
+        let (mut a, mut b) = (Rng::seeded(7), Rng::seeded(7));
+        for _ in 0..100 {
+            assert_eq!(a.float(), b.float());
+        }
+        assert_ne!(Rng::seeded(7).next(), Rng::seeded(8).next());
+    }
+
+    #[test]
+    fn values_stay_in_range() {
+        let mut rng = Rng::seeded(1);
+        for _ in 0..1000 {
+            assert!((0.0..1.0).contains(&rng.float()));
+            assert!(rng.below(6) < 6);
+        }
+    }
+
+    #[test]
+    fn dice_keep_highest_and_lowest() {
+        // Seed 7 rolls 1, 3, 1, 5 on the first four d6.
+        let dice = |count, keep| Dice {
+            count,
+            sides: 6,
+            keep,
+        };
+        let roll = |dice: Dice| dice.roll_with(&mut Rng::seeded(7));
+        assert_eq!(roll(dice(3, None)), BigInt::from(5));
+        assert_eq!(roll(dice(4, None)), BigInt::from(10));
+        assert_eq!(roll(dice(4, Some(Keep::Highest(3)))), BigInt::from(9));
+        assert_eq!(roll(dice(4, Some(Keep::Lowest(2)))), BigInt::from(2));
+    }
+}
@@ -0,0 +1,218 @@
+// The most steps each search takes, far more than a root that can be found needs.
+const ITERATIONS: usize = 200;
+
+// How many pieces an interval is cut into when looking for all of its roots.
+const SAMPLES: usize = 1000;
+
+// A root close to the guess. The secant method finds one quickly from a good guess, and
+// otherwise intervals growing out from the guess are searched for a sign change to close in on.
+pub fn near(f: impl Fn(f64) -> Result<f64, String>, guess: f64) -> Result<Option<f64>, String> {
+    if let Some(x) = secant(&f, guess)? {
+        return polish(&f, x).map(Some);
+    }
+
+    let step = 0.01 * guess.abs().max(1.0);
+    let mut inner = 0.0;
+    for k in 0..60 {
+        let outer = step * 1.5_f64.powi(k);
+        for (a, b) in [
+            (guess - outer, guess - inner),
+            (guess + inner, guess + outer),
+        ] {
+            if let Some(x) = bracket(&f, a, b)? {
+                return polish(&f, x).map(Some);
+            }
+        }
+        inner = outer;
+    }
+    Ok(None)
+}
+
+// Every root between a and b that the samples catch, either by a sign change between two of
+// them or by one of them dipping towards zero, as for x^2 = 0.
+pub fn all(f: impl Fn(f64) -> Result<f64, String>, a: f64, b: f64) -> Result<Vec<f64>, String> {
+    let xs: Vec<f64> = (0..=SAMPLES)
+        .map(|i| a + (b - a) * i as f64 / SAMPLES as f64)
+        .collect();
+    let ys = xs.iter().map(|&x| f(x)).collect::<Result<Vec<_>, _>>()?;
+
+    let mut roots = Vec::new();
+    for i in 0..SAMPLES {
+        if ys[i] == 0.0 {
+            roots.push(xs[i]);
+        } else if let Some(x) = bracket(&f, xs[i], xs[i + 1])? {
+            roots.push(x);
+        }
+    }
+    if ys[SAMPLES] == 0.0 {
+        roots.push(b);
+    }
+    for i in 1..SAMPLES {
+        let dip = ys[i].abs() < ys[i - 1].abs() && ys[i].abs() < ys[i + 1].abs();
+        if dip
+            && ys[i - 1].signum() == ys[i + 1].signum()
+            && let Some(x) = secant(&f, xs[i])?
+            && (xs[i - 1]..=xs[i + 1]).contains(&x)
+        {
+            roots.push(x);
+        }
+    }
+
+    let mut roots = roots
+        .into_iter()
+        .map(|x| polish(&f, x))
+        .collect::<Result<Vec<_>, _>>()?;
+    roots.sort_by(f64::total_cmp);
+    let close = 1e-9 * (b - a);
+    roots.dedup_by(|x, y| (*x - *y).abs() <= close);
+    Ok(roots)
+}
+
+// Prefers a rounder number, or zero for a tiny one, when it's at least as good a root, since
+// roots where f only touches zero, like x^2 = 0, can only be found to about half the digits.
+fn polish(f: &impl Fn(f64) -> Result<f64, String>, x: f64) -> Result<f64, String> {
+    if x == 0.0 {
+        return Ok(x);
+    }
+    let y = f(x)?.abs();
+    let digits = 10_f64.powi(11 - x.abs().log10().floor() as i32);
+    let zero = match x.abs() <= 1e-6 {
+        true => Some(0.0),
+        false => None,
+    };
+    for candidate in zero.into_iter().chain([(x * digits).round() / digits]) {
+        if candidate.is_finite() && f(candidate)?.abs() <= y {
+            return Ok(candidate);
+        }
+    }
+    Ok(x)
+}
+
+// Follows secants from the guess, returning where they settle if it's a root.
+fn secant(f: &impl Fn(f64) -> Result<f64, String>, guess: f64) -> Result<Option<f64>, String> {
+    let (mut x0, mut x1) = (guess, guess + 1e-4 * guess.abs().max(1.0));
+    let (mut f0, mut f1) = (f(x0)?, f(x1)?);
+    for _ in 0..ITERATIONS {
+        if f1 == 0.0 || f1 == f0 || !f1.is_finite() {
+            break;
+        }
+        let x2 = x1 - f1 * (x1 - x0) / (f1 - f0);
+        if !x2.is_finite() {
+            break;
+        }
+        (x0, f0) = (x1, f1);
+        (x1, f1) = (x2, f(x2)?);
+        if (x1 - x0).abs() <= 4.0 * f64::EPSILON * x1.abs().max(1.0) {
+            break;
+        }
+    }
+    Ok(is_root(f, x1)?.then_some(x1))
+}
+
+// Brent's method, which keeps a root inside a shrinking interval while taking the faster
+// secant and inverse quadratic steps whenever they stay well inside it. Returns None when
+// there's no sign change, or the change is a pole like tan's rather than a root.
+fn bracket(f: &impl Fn(f64) -> Result<f64, String>, a: f64, b: f64) -> Result<Option<f64>, String> {
+    let (mut a, mut b) = (a, b);
+    let (mut fa, mut fb) = (f(a)?, f(b)?);
+    if !fa.is_finite() || !fb.is_finite() || fa.signum() == fb.signum() {
+        return Ok(None);
+    }
+    if fa.abs() < fb.abs() {
+        (a, b, fa, fb) = (b, a, fb, fa);
+    }
+
+    let (mut c, mut fc) = (a, fa);
+    let mut d = c;
+    let mut bisected = true;
+    for _ in 0..ITERATIONS {
+        if fb == 0.0 || (b - a).abs() <= 4.0 * f64::EPSILON * b.abs().max(1.0) {
+            break;
+        }
+
+        let mut s = match fa != fc && fb != fc {
+            true => {
+                a * fb * fc / ((fa - fb) * (fa - fc))
+                    + b * fa * fc / ((fb - fa) * (fb - fc))
+                    + c * fa * fb / ((fc - fa) * (fc - fb))
+            }
+            false => b - fb * (b - a) / (fb - fa),
+        };
+        let quarter = (3.0 * a + b) / 4.0;
+        let previous = match bisected {
+            true => (b - c).abs(),
+            false => (c - d).abs(),
+        };
+        let outside = !(quarter.min(b)..=quarter.max(b)).contains(&s);
+        bisected = outside || (s - b).abs() >= previous / 2.0;
+        if bisected {
+            s = (a + b) / 2.0;
+        }
+
+        let fs = f(s)?;
+        d = c;
+        (c, fc) = (b, fb);
+        if fa.signum() != fs.signum() {
+            (b, fb) = (s, fs);
+        } else {
+            (a, fa) = (s, fs);
+        }
+        if fa.abs() < fb.abs() {
+            (a, b, fa, fb) = (b, a, fb, fa);
+        }
+    }
+    Ok(is_root(f, b)?.then_some(b))
+}
+
+// Whether f vanishes at x, or changes sign around it while being much smaller at x than on
+// either side, as it isn't at a pole or a jump, where it takes the size of one side. A tiny
+// value only counts while f still changes by as much within the step, since 1/x gets tiny
+// far out without ever reaching zero.
+fn is_root(f: &impl Fn(f64) -> Result<f64, String>, x: f64) -> Result<bool, String> {
+    let y = f(x)?;
+    if !y.is_finite() {
+        return Ok(false);
+    }
+    let h = 1e-8 * x.abs().max(1.0);
+    let (left, right) = (f(x - h)?, f(x + h)?);
+    Ok(y == 0.0
+        || (y.abs() <= 1e-12 && (left - right).abs() >= y.abs())
+        || (left.signum() != right.signum() && y.abs() <= 1e-3 * left.abs().min(right.abs())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{all, near};
+
+    #[test]
+    fn near_finds_hand_computed_roots() {
+        let root = near(|x| Ok(x * x - 2.0), 1.0).unwrap().unwrap();
+        assert!((root - 2_f64.sqrt()).abs() <= 1e-15);
+        // The Dottie number, where cos x = x.
+        let root = near(|x| Ok(x.cos() - x), 0.0).unwrap().unwrap();
+        assert!((root - 0.7390851332151607).abs() <= 1e-15);
+    }
+
+    #[test]
+    fn near_finds_nothing_without_a_root() {
+        assert_eq!(near(|x| Ok(x * x + 1.0), 0.0).unwrap(), None);
+        assert_eq!(near(|x| Ok(1.0 / x), 1.0).unwrap(), None);
+    }
+
+    #[test]
+    fn all_finds_every_root_in_range() {
+        let roots = all(|x| Ok(x.sin()), -1.0, 7.0).unwrap();
+        let expected = [0.0, std::f64::consts::PI, 2.0 * std::f64::consts::PI];
+        assert_eq!(roots.len(), expected.len());
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root - expected).abs() <= 1e-12);
+        }
+    }
+
+    #[test]
+    fn all_skips_poles_and_jumps() {
+        assert_eq!(all(|x| Ok(x.tan()), 1.0, 2.0).unwrap(), []);
+        assert_eq!(all(|x| Ok(1.0 / x), -1.0, 1.0).unwrap(), []);
+        assert_eq!(all(|x| Ok(x.floor() - 1.5), 0.0, 3.0).unwrap(), []);
+    }
+}
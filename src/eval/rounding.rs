@@ -0,0 +1,93 @@
+use crate::eval::{environment, unit};
+
+// How `round` and `roundto` break ties between two equally near values, set with `:round`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ties {
+    Away,
+    Even,
+    Zero,
+    Up,
+    Down,
+}
+
+impl Ties {
+    pub fn named(name: &str) -> Option<Ties> {
+        match name {
+            "away" => Some(Ties::Away),
+            "even" => Some(Ties::Even),
+            "zero" => Some(Ties::Zero),
+            "up" => Some(Ties::Up),
+            "down" => Some(Ties::Down),
+            _ => None,
+        }
+    }
+}
+
+// Values this large are already whole, with no fraction left to round.
+const WHOLE: f64 = 4503599627370496.0;
+
+// Rounds to a whole number. A value that's a tie when cut to the 15 digits that are shown is
+// taken for one, so that 2.675 * 100 counts as the tie it was written as rather than
+// 267.49999999999997, while other values keep every digit.
+pub fn whole(value: f64) -> f64 {
+    if value.abs() >= WHOLE {
+        return value;
+    }
+    let snapped = unit::round(value);
+    let floor = snapped.floor();
+    if snapped - floor != 0.5 {
+        return value.round();
+    }
+
+    match environment::fetch_settings().lock().unwrap().ties {
+        Ties::Away => snapped.round(),
+        Ties::Even if floor % 2.0 == 0.0 => floor,
+        Ties::Even => floor + 1.0,
+        Ties::Zero => snapped.trunc(),
+        Ties::Up => floor + 1.0,
+        Ties::Down => floor,
+    }
+}
+
+// Rounds to a number of decimal places, or to tens, hundreds and so on when it's negative. A
+// value with fewer places than asked for is left as it is, and rounding to a power of ten
+// beyond the largest number gives 0.
+pub fn places(value: f64, places: i32) -> f64 {
+    match places >= 0 {
+        true => {
+            let scale = 10f64.powi(places.min(f64::MAX_10_EXP + 1));
+            match (value * scale).abs() < WHOLE {
+                true => whole(value * scale) / scale,
+                false => value,
+            }
+        }
+        false if places < -f64::MAX_10_EXP => 0.0,
+        false => {
+            let scale = 10f64.powi(-places);
+            whole(value / scale) * scale
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{places, whole};
+
+    #[test]
+    fn whole_keeps_every_digit_apart_from_ties() {
+        assert_eq!(whole(2.0_f64.powi(60)), 2.0_f64.powi(60));
+        assert_eq!(whole(1234567890123456.7), 1234567890123457.0);
+        assert_eq!(whole(2.675 * 100.0), 268.0);
+        assert_eq!(whole(-2.5), -3.0);
+    }
+
+    #[test]
+    fn places_beyond_what_f64_holds() {
+        assert_eq!(places(0.12345678901234568, 18), 0.12345678901234568);
+        assert_eq!(places(123.456, 400), 123.456);
+        assert_eq!(places(2.5, i32::MAX), 2.5);
+        assert_eq!(places(5.0, -400), 0.0);
+        assert_eq!(places(1234.5678, -2), 1200.0);
+        assert_eq!(places(2.675, 2), 2.68);
+    }
+}
@@ -0,0 +1,134 @@
+// Neumaier's compensated sum, which keeps the low digits that a plain sum of many values loses.
+pub fn sum(xs: &[f64]) -> f64 {
+    let mut sum = 0.0;
+    let mut compensation = 0.0;
+    for &x in xs {
+        let t = sum + x;
+        compensation += match sum.abs() >= x.abs() {
+            true => (sum - t) + x,
+            false => (x - t) + sum,
+        };
+        sum = t;
+    }
+    sum + compensation
+}
+
+pub fn mean(xs: &[f64]) -> f64 {
+    sum(xs) / xs.len() as f64
+}
+
+// Welford's method, which avoids subtracting two large sums of squares. `ddof` is 1 for the
+// sample variance and 0 for the population variance.
+pub fn variance(xs: &[f64], ddof: usize) -> Result<f64, String> {
+    if xs.len() <= ddof {
+        let kind = match ddof {
+            0 => "population",
+            _ => "sample",
+        };
+        return Err(format!(
+            "Not enough values: The {} variance needs at least {} values",
+            kind,
+            ddof + 1
+        ));
+    }
+
+    let mut mean = 0.0;
+    let mut squares = 0.0;
+    for (i, &x) in xs.iter().enumerate() {
+        let delta = x - mean;
+        mean += delta / (i + 1) as f64;
+        squares += delta * (x - mean);
+    }
+    Ok(squares / (xs.len() - ddof) as f64)
+}
+
+// Linear interpolation between the closest ranks, the default in most spreadsheets.
+pub fn quantile(xs: &[f64], p: f64) -> Result<f64, String> {
+    if !(0.0..=1.0).contains(&p) {
+        return Err(format!(
+            "Invalid quantile '{}': Expected a number between 0 and 1",
+            p
+        ));
+    }
+
+    let sorted = sorted(xs);
+    let rank = p * (sorted.len() - 1) as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    Ok(sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64))
+}
+
+// The most common value, or the smallest of them when several are equally common.
+pub fn mode(xs: &[f64]) -> f64 {
+    let sorted = sorted(xs);
+    let (mut best, mut best_count) = (sorted[0], 0);
+    let mut i = 0;
+    while i < sorted.len() {
+        let count = sorted[i..]
+            .iter()
+            .take_while(|x| x.total_cmp(&sorted[i]).is_eq())
+            .count();
+        if count > best_count {
+            (best, best_count) = (sorted[i], count);
+        }
+        i += count;
+    }
+    best
+}
+
+pub fn geomean(xs: &[f64]) -> Result<f64, String> {
+    if let Some(x) = xs.iter().find(|&&x| x <= 0.0) {
+        return Err(format!(
+            "Invalid value '{}' for 'geomean': Expected positive numbers",
+            x
+        ));
+    }
+
+    let logs: Vec<f64> = xs.iter().map(|x| x.ln()).collect();
+    Ok(mean(&logs).exp())
+}
+
+pub fn harmean(xs: &[f64]) -> Result<f64, String> {
+    if let Some(x) = xs.iter().find(|&&x| x <= 0.0) {
+        return Err(format!(
+            "Invalid value '{}' for 'harmean': Expected positive numbers",
+            x
+        ));
+    }
+
+    let inverses: Vec<f64> = xs.iter().map(|x| 1.0 / x).collect();
+    Ok(xs.len() as f64 / sum(&inverses))
+}
+
+fn sorted(xs: &[f64]) -> Vec<f64> {
+    let mut sorted = xs.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mode, quantile, variance};
+
+    #[test]
+    fn mode_takes_smallest_of_most_common() {
+        assert_eq!(mode(&[3.0, 1.0, 3.0, 1.0, 2.0]), 1.0);
+        assert_eq!(mode(&[5.0]), 5.0);
+        assert!(mode(&[1.0, f64::NAN, f64::NAN]).is_nan());
+    }
+
+    #[test]
+    fn quantile_interpolates_between_ranks() {
+        assert_eq!(quantile(&[1.0, 2.0, 3.0, 4.0], 0.5), Ok(2.5));
+        assert_eq!(quantile(&[4.0, 1.0, 3.0], 0.5), Ok(3.0));
+        assert!(quantile(&[1.0, 2.0], 1.5).is_err());
+    }
+
+    #[test]
+    fn variance_of_sample_and_population() {
+        let xs = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert_eq!(variance(&xs, 0), Ok(4.0));
+        assert_eq!(variance(&xs, 1), Ok(32.0 / 7.0));
+        assert!(variance(&[1.0], 1).is_err());
+    }
+}
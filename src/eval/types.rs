@@ -1,12 +1,21 @@
+use num_bigint::BigInt;
+
+use crate::eval::{date::Date, integer::Integer, matrix, random::Dice, unit::Quantity};
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Num(f64),
+    Int(BigInt),
+    Date(Date),
+    Time(f64),
+    Dice(Dice),
     Identifier(String),
 
     Plus,
     Minus,
     Star,
     Slash,
+    DoubleSlash,
     Carrot,
     LeftParen,
     RightParen,
@@ -14,6 +23,21 @@ pub enum Token {
     Percent,
     Bar,
     Equal,
+    To,
+    Ampersand,
+    Tilde,
+    ShiftLeft,
+    ShiftRight,
+    Xor,
+    Mod,
+    Rem,
+    Arrow,
+    Of,
+    As,
+    LeftBracket,
+    RightBracket,
+    DotDot,
+    Step,
 
     Comma,
     Underscore,
@@ -22,10 +46,17 @@ pub enum Token {
 impl Token {
     pub fn precedence(&self) -> u8 {
         match self {
-            Token::Plus | Token::Minus => 1,
-            Token::Star | Token::Slash => 2,
-            Token::Carrot => 3,
-            Token::Exclamation => 4,
+            Token::To | Token::Arrow | Token::As | Token::DotDot => 1,
+            Token::Bar => 2,
+            Token::Xor => 3,
+            Token::Ampersand => 4,
+            Token::ShiftLeft | Token::ShiftRight => 5,
+            Token::Plus | Token::Minus | Token::Num(_) | Token::Int(_) => 6,
+            Token::Star | Token::Slash | Token::DoubleSlash | Token::Mod | Token::Rem => 7,
+            Token::Carrot => 8,
+            Token::Exclamation | Token::Percent => 9,
+            Token::LeftBracket | Token::LeftParen => 10,
+            Token::Identifier(_) => 7,
             _ => 0,
         }
     }
@@ -35,11 +66,20 @@ impl std::fmt::Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let s = match self {
             Token::Num(n) => &n.to_string(),
+            Token::Int(n) => &n.to_string(),
+            Token::Date(d) => &d.to_string(),
+            Token::Time(t) => &format!(
+                "{}:{:02}",
+                (t / 3600.0).floor(),
+                (t % 3600.0 / 60.0).floor()
+            ),
+            Token::Dice(d) => &d.to_string(),
             Token::Identifier(i) => &i.to_string(),
             Token::Plus => "+",
             Token::Minus => "-",
             Token::Star => "*",
             Token::Slash => "/",
+            Token::DoubleSlash => "//",
             Token::Carrot => "^",
             Token::LeftParen => "(",
             Token::RightParen => ")",
@@ -49,6 +89,21 @@ impl std::fmt::Display for Token {
             Token::Underscore => "_",
             Token::Bar => "|",
             Token::Equal => "=",
+            Token::To => "to",
+            Token::Ampersand => "&",
+            Token::Tilde => "~",
+            Token::ShiftLeft => "<<",
+            Token::ShiftRight => ">>",
+            Token::Xor => "xor",
+            Token::Mod => "mod",
+            Token::Rem => "rem",
+            Token::Arrow => "->",
+            Token::Of => "of",
+            Token::As => "as",
+            Token::LeftBracket => "[",
+            Token::RightBracket => "]",
+            Token::DotDot => "..",
+            Token::Step => "step",
         };
         write!(f, "{}", s)
     }
@@ -57,10 +112,212 @@ impl std::fmt::Display for Token {
 #[derive(Debug, Clone)]
 pub enum Expr {
     Num(f64),
+    Int(BigInt),
+    Date(Date),
+    Dice(Dice),
+    Unit(String),
     Var(String, Box<Expr>),
-    Func(String, Vec<Box<Expr>>),
+    Func(String, Vec<Expr>),
     Bin(Box<Expr>, Operator, Box<Expr>),
     Unary(Operator, Box<Expr>),
+    List(Vec<Expr>),
+    Range(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    Index(Box<Expr>, Box<Expr>),
+    Lambda(Vec<String>, Box<Expr>),
+    Param(String),
+    Call(Box<Expr>, Vec<Expr>),
+    Value(Value),
+}
+
+impl Expr {
+    // Replaces a lambda's parameter with the value it was called with, leaving alone any inner
+    // lambda that has a parameter of the same name.
+    pub fn substitute(self, name: &str, value: &Value) -> Expr {
+        let sub = |expr: Box<Expr>| Box::new(expr.substitute(name, value));
+        let subs = |exprs: Vec<Expr>| {
+            exprs
+                .into_iter()
+                .map(|expr| expr.substitute(name, value))
+                .collect()
+        };
+
+        match self {
+            Expr::Param(id) if id == name => Expr::Value(value.clone()),
+            Expr::Lambda(params, body) if !params.iter().any(|param| param == name) => {
+                Expr::Lambda(params, sub(body))
+            }
+            Expr::Func(id, args) => Expr::Func(id, subs(args)),
+            Expr::Bin(left, op, right) => Expr::Bin(sub(left), op, sub(right)),
+            Expr::Unary(op, side) => Expr::Unary(op, sub(side)),
+            Expr::List(items) => Expr::List(subs(items)),
+            Expr::Range(start, end, step) => Expr::Range(sub(start), sub(end), step.map(sub)),
+            Expr::Index(list, index) => Expr::Index(sub(list), sub(index)),
+            Expr::Call(callee, args) => Expr::Call(sub(callee), subs(args)),
+            expr => expr,
+        }
+    }
+
+    // Whether working it out rolls dice or draws random numbers, so that it can give a different
+    // value each time.
+    pub fn random(&self) -> bool {
+        let any = |exprs: &[Expr]| exprs.iter().any(Expr::random);
+        match self {
+            Expr::Dice(_) => true,
+            Expr::Func(id, _) if matches!(id.as_str(), "rand" | "randn" | "randint") => true,
+            Expr::Func(_, args) | Expr::List(args) => any(args),
+            Expr::Var(_, value) | Expr::Unary(_, value) | Expr::Lambda(_, value) => value.random(),
+            Expr::Bin(left, _, right) | Expr::Index(left, right) => left.random() || right.random(),
+            Expr::Range(start, end, step) => {
+                start.random() || end.random() || step.as_ref().is_some_and(|s| s.random())
+            }
+            Expr::Call(callee, args) => callee.random() || any(args),
+            _ => false,
+        }
+    }
+}
+
+// Functions as values, which can be passed to map, filter and reduce or composed.
+#[derive(Debug, Clone)]
+pub enum Function {
+    Lambda(Vec<String>, Box<Expr>),
+    Builtin(String),
+    Compose(Vec<Function>),
+}
+
+impl std::fmt::Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Function::Lambda(params, body) if params.len() == 1 => {
+                write!(f, "{} -> {}", params[0], body)
+            }
+            Function::Lambda(params, body) => write!(f, "({}) -> {}", params.join(", "), body),
+            Function::Builtin(id) => write!(f, "{}", id),
+            Function::Compose(functions) => {
+                let functions: Vec<String> = functions.iter().map(|f| f.to_string()).collect();
+                write!(f, "compose({})", functions.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Quantity(Quantity),
+    Int(Integer),
+    Date(Date),
+    Text(String),
+    List(Vec<Value>),
+    Function(Function),
+}
+
+impl Value {
+    pub fn quantity(self) -> Result<Quantity, String> {
+        match self {
+            Value::Quantity(q) => Ok(q),
+            Value::Int(i) => Ok(match i.radix {
+                Some(base) => Quantity::from(i.to_f64()).with_radix(base),
+                None => Quantity::from(i.to_f64()),
+            }),
+            Value::Date(d) => Err(format!(
+                "Unexpected date '{}': Expected a number or quantity here",
+                d
+            )),
+            Value::Text(_) => Err("Unexpected text: Expected a number or quantity here".into()),
+            Value::List(_) => Err("Unexpected list: Expected a number or quantity here".into()),
+            Value::Function(function) => Err(format!(
+                "Unexpected function '{}': Expected a number or quantity here",
+                function
+            )),
+        }
+    }
+
+    pub fn date(self) -> Result<Date, String> {
+        match self {
+            Value::Date(d) => Ok(d),
+            Value::Quantity(q) => Err(format!("Unexpected '{}': Expected a date here", q)),
+            Value::Int(i) => Err(format!("Unexpected '{}': Expected a date here", i)),
+            Value::Text(_) => Err("Unexpected text: Expected a date here".into()),
+            Value::List(_) => Err("Unexpected list: Expected a date here".into()),
+            Value::Function(function) => Err(format!(
+                "Unexpected function '{}': Expected a date here",
+                function
+            )),
+        }
+    }
+
+    // Whole numbers without a unit, for bitwise operators.
+    pub fn integer(self) -> Result<Integer, String> {
+        match self {
+            Value::Int(i) => Ok(i),
+            Value::Quantity(q) if q.dim.is_none() => Integer::from_f64(q.value)
+                .ok_or(format!("Unexpected '{}': Expected a whole number here", q)),
+            value => Err(format!(
+                "Unexpected '{}': Expected a whole number without a unit here",
+                value
+            )),
+        }
+    }
+}
+
+impl From<Quantity> for Value {
+    fn from(q: Quantity) -> Self {
+        Value::Quantity(q)
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Quantity(q) => write!(f, "{}", q),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Text(s) => write!(f, "{}", s),
+            Value::Date(d) => write!(f, "{}", d),
+            // Matrices show a row per line, with the columns lined up.
+            Value::List(rows) if rows.len() > 1 && matrix::is_matrix(rows) => {
+                let cells: Vec<Vec<String>> = rows
+                    .iter()
+                    .map(|row| match row {
+                        Value::List(row) => row.iter().map(|x| x.to_string()).collect(),
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                let widths: Vec<usize> = (0..cells[0].len())
+                    .map(|j| {
+                        cells
+                            .iter()
+                            .map(|row| row[j].chars().count())
+                            .max()
+                            .unwrap()
+                    })
+                    .collect();
+                let lines: Vec<String> = cells
+                    .iter()
+                    .map(|row| {
+                        let row: Vec<String> = row
+                            .iter()
+                            .zip(widths.iter())
+                            .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+                            .collect();
+                        format!("[{}]", row.join(", "))
+                    })
+                    .collect();
+                write!(f, "[{}]", lines.join(",\n "))
+            }
+            Value::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                // Lists of matrices, like the results of decompositions, put each on its own lines.
+                match items.iter().any(|item| item.contains('\n')) {
+                    true => {
+                        let items: Vec<String> =
+                            items.iter().map(|item| item.replace('\n', "\n ")).collect();
+                        write!(f, "[{}]", items.join(",\n "))
+                    }
+                    false => write!(f, "[{}]", items.join(", ")),
+                }
+            }
+            Value::Function(function) => write!(f, "{}", function),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -69,9 +326,150 @@ pub enum Operator {
     Subtraction,
     Multiplication,
     Division,
+    FloorDivision,
+    Modulo,
+    Remainder,
     Exponent,
     Factorial(i8),
     Percent,
     Absolute,
-    Equal
+    Equal,
+    Conversion,
+    Change,
+    Proportion,
+    Radix(u32),
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    ShiftLeft,
+    ShiftRight,
+}
+
+impl Operator {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Operator::Conversion | Operator::Change | Operator::Proportion => 1,
+            Operator::BitOr => 2,
+            Operator::BitXor => 3,
+            Operator::BitAnd => 4,
+            Operator::ShiftLeft | Operator::ShiftRight => 5,
+            Operator::Addition | Operator::Subtraction => 6,
+            Operator::Multiplication
+            | Operator::Division
+            | Operator::FloorDivision
+            | Operator::Modulo
+            | Operator::Remainder
+            | Operator::Percent => 7,
+            Operator::Exponent => 8,
+            _ => 0,
+        }
+    }
+
+    pub fn bitwise(&self) -> bool {
+        matches!(
+            self,
+            Operator::BitAnd
+                | Operator::BitOr
+                | Operator::BitXor
+                | Operator::BitNot
+                | Operator::ShiftLeft
+                | Operator::ShiftRight
+        )
+    }
+}
+
+impl std::fmt::Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            Operator::Addition => "+",
+            Operator::Subtraction => "-",
+            Operator::Multiplication => "*",
+            Operator::Division => "/",
+            Operator::FloorDivision => " // ",
+            Operator::Modulo => " mod ",
+            Operator::Remainder => " rem ",
+            Operator::Exponent => "^",
+            Operator::Factorial(_) => "!",
+            Operator::Percent => "% of ",
+            Operator::Absolute => "|",
+            Operator::Equal => " = ",
+            Operator::Conversion => " to ",
+            Operator::Change => " -> ",
+            Operator::Proportion => " as % of ",
+            Operator::Radix(_) => " to base ",
+            Operator::BitAnd => " & ",
+            Operator::BitOr => " | ",
+            Operator::BitXor => " xor ",
+            Operator::BitNot => "~",
+            Operator::ShiftLeft => " << ",
+            Operator::ShiftRight => " >> ",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let wrap = |expr: &Expr, precedence: u8| -> String {
+            match expr {
+                Expr::Bin(_, op, _) if op.precedence() < precedence => format!("({})", expr),
+                Expr::Lambda(..) | Expr::Range(..) if precedence > 1 => format!("({})", expr),
+                _ => expr.to_string(),
+            }
+        };
+
+        match self {
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Int(n) => write!(f, "{}", n),
+            Expr::Date(d) => write!(f, "{}", d),
+            Expr::Dice(d) => write!(f, "{}", d),
+            Expr::Unit(id) | Expr::Var(id, _) => write!(f, "{}", id),
+            Expr::Func(id, args) => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{}({})", id, args.join(", "))
+            }
+            Expr::Bin(left, Operator::Multiplication, right)
+                if matches!(**left, Expr::Num(_) | Expr::Int(_))
+                    && matches!(**right, Expr::Unit(_)) =>
+            {
+                write!(f, "{} {}", left, right)
+            }
+            Expr::Bin(left, op, right) => write!(
+                f,
+                "{}{}{}",
+                wrap(left, op.precedence()),
+                op,
+                wrap(right, op.precedence() + 1)
+            ),
+            Expr::Unary(Operator::Factorial(amount), side) => {
+                write!(f, "{}{}", wrap(side, 9), "!".repeat(*amount as usize))
+            }
+            Expr::Unary(Operator::Percent, side) => write!(f, "{}%", wrap(side, 9)),
+            Expr::Unary(Operator::Absolute, side) => write!(f, "|{}|", side),
+            Expr::Unary(Operator::Radix(base), side) => write!(f, "{} to base {}", side, base),
+            Expr::Unary(op, side) => write!(f, "{}{}", op, wrap(side, 9)),
+            Expr::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Expr::Range(start, end, step) => {
+                write!(f, "{}..{}", wrap(start, 2), wrap(end, 2))?;
+                match step {
+                    Some(step) => write!(f, " step {}", wrap(step, 2)),
+                    None => Ok(()),
+                }
+            }
+            Expr::Index(list, index) => write!(f, "{}[{}]", wrap(list, 10), index),
+            Expr::Lambda(params, body) => {
+                write!(f, "{}", Function::Lambda(params.clone(), body.clone()))
+            }
+            Expr::Param(id) => write!(f, "{}", id),
+            Expr::Call(callee, args) => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{}({})", wrap(callee, 10), args.join(", "))
+            }
+            Expr::Value(value) => write!(f, "{}", value),
+        }
+    }
 }
@@ -0,0 +1,399 @@
+use std::collections::BTreeMap;
+
+use crate::eval::{currency, environment, radix};
+
+pub mod prefix;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Dimension(BTreeMap<String, i32>);
+
+impl Dimension {
+    pub fn is_none(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, base: &str) -> bool {
+        self.0.contains_key(base)
+    }
+
+    pub fn powi(&self, n: i32) -> Self {
+        if n == 0 {
+            return Dimension::default();
+        }
+
+        Dimension(self.0.iter().map(|(k, v)| (k.clone(), v * n)).collect())
+    }
+
+    pub fn root(&self, n: i32) -> Option<Self> {
+        if self.0.values().any(|v| v % n != 0) {
+            return None;
+        }
+
+        Some(Dimension(
+            self.0.iter().map(|(k, v)| (k.clone(), v / n)).collect(),
+        ))
+    }
+
+    fn combine(mut self, other: Dimension, sign: i32) -> Self {
+        for (k, v) in other.0 {
+            let exponent = self.0.entry(k).or_insert(0);
+            *exponent += v * sign;
+        }
+
+        self.0.retain(|_, v| *v != 0);
+        self
+    }
+}
+
+impl From<&[(&str, i32)]> for Dimension {
+    fn from(parts: &[(&str, i32)]) -> Self {
+        Dimension(
+            parts
+                .iter()
+                .filter(|(_, v)| *v != 0)
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+        )
+    }
+}
+
+impl std::ops::Mul for Dimension {
+    type Output = Dimension;
+
+    fn mul(self, other: Dimension) -> Dimension {
+        self.combine(other, 1)
+    }
+}
+
+impl std::ops::Div for Dimension {
+    type Output = Dimension;
+
+    fn div(self, other: Dimension) -> Dimension {
+        self.combine(other, -1)
+    }
+}
+
+impl std::fmt::Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let join = |parts: Vec<String>| -> String {
+            if parts.len() > 1 {
+                format!("({})", parts.join("*"))
+            } else {
+                parts.join("*")
+            }
+        };
+        let part = |k: &str, v: i32| -> String {
+            if v == 1 {
+                k.to_string()
+            } else {
+                format!("{}^{}", k, v)
+            }
+        };
+
+        let numerator: Vec<String> = self
+            .0
+            .iter()
+            .filter(|(_, v)| **v > 0)
+            .map(|(k, v)| part(k, *v))
+            .collect();
+        let denominator: Vec<String> = self
+            .0
+            .iter()
+            .filter(|(_, v)| **v < 0)
+            .map(|(k, v)| part(k, -v))
+            .collect();
+
+        match (numerator.is_empty(), denominator.is_empty()) {
+            (_, true) => write!(f, "{}", numerator.join("*")),
+            (true, false) => write!(f, "1/{}", join(denominator)),
+            (false, false) => write!(f, "{}/{}", numerator.join("*"), join(denominator)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prefix {
+    None,
+    Metric,
+    Binary,
+}
+
+#[derive(Debug, Clone)]
+pub struct Unit {
+    pub name: String,
+    pub factor: f64,
+    pub offset: f64,
+    pub dim: Dimension,
+    pub prefix: Prefix,
+}
+
+impl Unit {
+    pub fn new(name: &str, factor: f64, dim: &[(&str, i32)], prefix: Prefix) -> Self {
+        Unit::affine(name, factor, 0.0, dim, prefix)
+    }
+
+    // Units like °C whose zero point is shifted away from the base unit's zero.
+    pub fn affine(
+        name: &str,
+        factor: f64,
+        offset: f64,
+        dim: &[(&str, i32)],
+        prefix: Prefix,
+    ) -> Self {
+        Unit {
+            name: name.to_string(),
+            factor,
+            offset,
+            dim: Dimension::from(dim),
+            prefix,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Quantity {
+    pub value: f64,
+    pub dim: Dimension,
+    pub unit: Option<Box<Unit>>,
+    pub radix: Option<u32>,
+}
+
+impl Quantity {
+    pub fn new(value: f64, dim: Dimension) -> Self {
+        Quantity {
+            value,
+            dim,
+            unit: None,
+            radix: None,
+        }
+    }
+
+    pub fn with_radix(self, radix: u32) -> Self {
+        Quantity {
+            radix: Some(radix),
+            ..self
+        }
+    }
+
+    pub fn convert(self, unit: Unit) -> Result<Quantity, String> {
+        if self.dim != unit.dim {
+            return Err(format!(
+                "Incompatible units: Cannot convert '{}' to '{}'",
+                Quantity::unit_name(&self.dim),
+                unit.name
+            ));
+        }
+
+        Ok(Quantity {
+            unit: Some(Box::new(unit)),
+            ..self
+        })
+    }
+
+    pub fn scalar(&self) -> Result<f64, String> {
+        if !self.dim.is_none() {
+            return Err(format!(
+                "Unexpected unit '{}': Expected a dimensionless number here",
+                self.dim
+            ));
+        }
+
+        Ok(self.value)
+    }
+
+    pub fn try_add(self, other: Quantity) -> Result<Quantity, String> {
+        self.compatible(&other, "add")?;
+        Ok(Quantity::new(self.value + other.value, self.dim))
+    }
+
+    pub fn try_sub(self, other: Quantity) -> Result<Quantity, String> {
+        self.compatible(&other, "subtract")?;
+        Ok(Quantity::new(self.value - other.value, self.dim))
+    }
+
+    pub fn try_pow(self, other: Quantity) -> Result<Quantity, String> {
+        let exponent = other.scalar()?;
+        if self.dim.is_none() {
+            return Ok(Quantity::from(self.value.powf(exponent)));
+        }
+
+        if exponent.fract() == 0.0 {
+            return Ok(Quantity::new(
+                self.value.powf(exponent),
+                self.dim.powi(exponent as i32),
+            ));
+        }
+
+        let n = (1.0 / exponent).round();
+        match self.dim.root(n as i32) {
+            Some(dim) if (1.0 / n - exponent).abs() < 1e-12 => {
+                Ok(Quantity::new(self.value.powf(exponent), dim))
+            }
+            _ => Err(format!(
+                "Invalid exponent '{}': Raising '{}' to it would give fractional units",
+                exponent, self.dim
+            )),
+        }
+    }
+
+    pub fn compatible(&self, other: &Quantity, action: &str) -> Result<(), String> {
+        if self.dim != other.dim {
+            return Err(format!(
+                "Incompatible units: Cannot {} '{}' and '{}'",
+                action,
+                Quantity::unit_name(&self.dim),
+                Quantity::unit_name(&other.dim)
+            ));
+        }
+
+        Ok(())
+    }
+
+    // A temperature in an affine unit like °C is a point on a scale that doesn't start at zero,
+    // so sums and products of it only make sense once it's converted to K.
+    pub fn absolute(&self) -> Result<(), String> {
+        match &self.unit {
+            Some(unit) if unit.offset != 0.0 => Err(format!(
+                "Affine unit: Cannot calculate with '{}' since {} doesn't start at zero, convert it to K first",
+                self, unit.name
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn unit_name(dim: &Dimension) -> String {
+        if dim.is_none() {
+            return "a dimensionless number".into();
+        }
+
+        simplify(dim).unwrap_or_else(|| dim.to_string())
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(value: f64) -> Self {
+        Quantity::new(value, Dimension::default())
+    }
+}
+
+impl std::ops::Mul for Quantity {
+    type Output = Quantity;
+
+    fn mul(self, other: Quantity) -> Quantity {
+        Quantity::new(self.value * other.value, self.dim * other.dim)
+    }
+}
+
+impl std::ops::Div for Quantity {
+    type Output = Quantity;
+
+    fn div(self, other: Quantity) -> Quantity {
+        Quantity::new(self.value / other.value, self.dim / other.dim)
+    }
+}
+
+impl std::ops::Neg for Quantity {
+    type Output = Quantity;
+
+    fn neg(self) -> Quantity {
+        Quantity::new(-self.value, self.dim)
+    }
+}
+
+impl std::fmt::Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let base = self
+            .radix
+            .unwrap_or_else(|| environment::fetch_settings().lock().unwrap().base);
+        let number = |value: f64| match base {
+            10 => tidy(value).to_string(),
+            _ => radix::format(value, base),
+        };
+
+        if let Some(unit) = &self.unit {
+            write!(
+                f,
+                "{} {}",
+                number((self.value - unit.offset) / unit.factor),
+                unit.name
+            )?;
+        } else if self.dim.is_none() {
+            write!(f, "{}", number(self.value))?;
+        } else {
+            write!(
+                f,
+                "{} {}",
+                number(self.value),
+                Quantity::unit_name(&self.dim)
+            )?;
+        }
+
+        match currency::note(&self.dim) {
+            Some(note) => write!(f, " {}", note),
+            None => Ok(()),
+        }
+    }
+}
+
+// Cuts a value to 15 significant digits, dropping the last couple that conversions leave noise in.
+pub fn round(value: f64) -> f64 {
+    format!("{:.14e}", value).parse().unwrap_or(value)
+}
+
+// Conversion noise like 211.99999999999997 is only dropped when what's left is a much shorter
+// number, as 212 is, so that other values keep every digit.
+fn tidy(value: f64) -> f64 {
+    let rounded = round(value);
+    let mantissa = format!("{:e}", rounded);
+    let digits = mantissa
+        .split('e')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(char::is_ascii_digit)
+        .count();
+    match digits <= 12 {
+        true => rounded,
+        false => value,
+    }
+}
+
+// Derived units that a result is shown in when its dimension matches exactly.
+const SIMPLIFIED: [&str; 12] = ["N", "J", "W", "Pa", "C", "V", "F", "Ω", "S", "Wb", "T", "H"];
+
+fn simplify(dim: &Dimension) -> Option<String> {
+    let units = environment::fetch_units().lock().unwrap();
+    SIMPLIFIED
+        .iter()
+        .filter_map(|name| units.iter().find(|unit| unit.name == *name))
+        .find(|unit| unit.factor == 1.0 && &unit.dim == dim)
+        .map(|unit| unit.name.clone())
+}
+
+pub fn find(id: &str) -> Option<Unit> {
+    let units = environment::fetch_units().lock().unwrap();
+    if let Some(unit) = units.iter().find(|unit| unit.name == id) {
+        return Some(unit.clone());
+    }
+
+    for (symbol, scale, kind) in prefix::PREFIXES {
+        let Some(name) = id.strip_prefix(symbol) else {
+            continue;
+        };
+
+        let unit = units.iter().find(|unit| {
+            unit.name == name
+                && (unit.prefix == kind
+                    || (unit.prefix == Prefix::Binary && kind == Prefix::Metric))
+        });
+        if let Some(unit) = unit {
+            return Some(Unit {
+                name: id.to_string(),
+                factor: scale * unit.factor,
+                ..unit.clone()
+            });
+        }
+    }
+
+    None
+}
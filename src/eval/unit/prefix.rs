@@ -0,0 +1,36 @@
+use crate::eval::unit::Prefix;
+
+// Longer symbols come first so that "da" is tried before "d".
+pub const PREFIXES: [(&str, f64, Prefix); 31] = [
+    ("Ki", 1024.0, Prefix::Binary),
+    ("Mi", 1048576.0, Prefix::Binary),
+    ("Gi", 1073741824.0, Prefix::Binary),
+    ("Ti", 1099511627776.0, Prefix::Binary),
+    ("Pi", 1125899906842624.0, Prefix::Binary),
+    ("Ei", 1152921504606846976.0, Prefix::Binary),
+    ("da", 1e1, Prefix::Metric),
+    ("Q", 1e30, Prefix::Metric),
+    ("R", 1e27, Prefix::Metric),
+    ("Y", 1e24, Prefix::Metric),
+    ("Z", 1e21, Prefix::Metric),
+    ("E", 1e18, Prefix::Metric),
+    ("P", 1e15, Prefix::Metric),
+    ("T", 1e12, Prefix::Metric),
+    ("G", 1e9, Prefix::Metric),
+    ("M", 1e6, Prefix::Metric),
+    ("k", 1e3, Prefix::Metric),
+    ("h", 1e2, Prefix::Metric),
+    ("d", 1e-1, Prefix::Metric),
+    ("c", 1e-2, Prefix::Metric),
+    ("m", 1e-3, Prefix::Metric),
+    ("µ", 1e-6, Prefix::Metric),
+    ("u", 1e-6, Prefix::Metric),
+    ("n", 1e-9, Prefix::Metric),
+    ("p", 1e-12, Prefix::Metric),
+    ("f", 1e-15, Prefix::Metric),
+    ("a", 1e-18, Prefix::Metric),
+    ("z", 1e-21, Prefix::Metric),
+    ("y", 1e-24, Prefix::Metric),
+    ("r", 1e-27, Prefix::Metric),
+    ("q", 1e-30, Prefix::Metric),
+];
@@ -2,5 +2,22 @@ mod calc;
 mod eval;
 
 fn main() {
-    calc::run();
+    if let Ok(path) = std::env::var("RUN_RATES")
+        && let Err(e) = eval::currency::load(&path)
+    {
+        eprintln!("=> {}", e);
+    }
+
+    let paths: Vec<String> = std::env::args().skip(1).collect();
+    if paths.is_empty() {
+        calc::run();
+        return;
+    }
+
+    for path in paths {
+        if let Err(e) = calc::script(&path) {
+            eprintln!("=> {}", e);
+            std::process::exit(1);
+        }
+    }
 }